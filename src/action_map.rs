@@ -0,0 +1,332 @@
+use crate::event::{Event, MouseButton, ScanCode};
+use crate::keys::key_to_str;
+use glium::glutin::event::VirtualKeyCode;
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::Hash;
+
+#[cfg(feature = "gilrs")]
+use crate::event::GamepadButton;
+
+/// Input that can be bound to an action. When the `serde` feature is enabled, bindings can be
+/// serialized (ex. to save user key bindings). Keys are serialized using their names (see
+/// key_to_str).
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub enum Binding {
+    Key(VirtualKeyCode),
+    /// Key by physical location (ex. for WASD controls that work on any keyboard layout).
+    ScanCode(ScanCode),
+    Mouse(MouseButton),
+    /// Button on any connected gamepad.
+    #[cfg(feature = "gilrs")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Gamepad(GamepadButton),
+}
+impl fmt::Display for Binding {
+    /// Short name of the input (ex. for "Press [E] to interact" prompts).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => f.write_str(key_to_str(*key)),
+            Binding::ScanCode(code) => write!(f, "Scan{}", code),
+            Binding::Mouse(MouseButton::Left) => f.write_str("MouseLeft"),
+            Binding::Mouse(MouseButton::Right) => f.write_str("MouseRight"),
+            Binding::Mouse(MouseButton::Middle) => f.write_str("MouseMiddle"),
+            Binding::Mouse(MouseButton::Other(n)) => write!(f, "Mouse{}", n),
+            #[cfg(feature = "gilrs")]
+            Binding::Gamepad(button) => write!(f, "{:?}", button),
+        }
+    }
+}
+
+/// Maps keys, mouse buttons and gamepad buttons to application-defined actions, so that controls
+/// can be rebound. An action is pressed while any of its bindings are held down.
+///
+/// # Examples
+///
+/// ```
+/// #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+/// enum Action {
+///     Jump,
+///     Fire,
+/// }
+///
+/// let mut actions = glu::ActionMap::new();
+/// actions.bind(Action::Jump, glu::Binding::Key(glu::VirtualKeyCode::Space));
+/// actions.bind(Action::Fire, glu::Binding::Mouse(glu::MouseButton::Left));
+/// win.run(move |display, e, event_state| {
+///     actions.handle(&e);
+///     if let glu::Event::Redraw = e {
+///         if actions.just_pressed(Action::Jump) {
+///             // Jump...
+///         }
+///         actions.end_frame();
+///     }
+///     glu::WindowState::Run
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct ActionMap<A> {
+    bindings: Vec<(A, Binding)>,
+    held: HashSet<Binding>,
+    pressed: HashSet<A>,
+    just_pressed: HashSet<A>,
+    just_released: HashSet<A>,
+}
+
+impl<A: Copy + Eq + Hash> ActionMap<A> {
+    pub fn new() -> Self {
+        Self {
+            bindings: Vec::new(),
+            held: HashSet::new(),
+            pressed: HashSet::new(),
+            just_pressed: HashSet::new(),
+            just_released: HashSet::new(),
+        }
+    }
+
+    /// Binds the input to the action. An action can have any number of bindings, and a binding can
+    /// trigger more than one action.
+    pub fn bind(&mut self, action: A, binding: Binding) {
+        if !self.bindings.contains(&(action, binding.clone())) {
+            self.bindings.push((action, binding));
+            self.update_action(action);
+        }
+    }
+    /// Removes the binding from the action.
+    pub fn unbind(&mut self, action: A, binding: &Binding) {
+        self.bindings.retain(|(a, b)| *a != action || b != binding);
+        self.update_action(action);
+    }
+    /// Removes all bindings from the action.
+    pub fn unbind_all(&mut self, action: A) {
+        self.bindings.retain(|(a, _)| *a != action);
+        self.update_action(action);
+    }
+    /// Bindings of the action, in the order they were bound.
+    pub fn bindings(&self, action: A) -> impl Iterator<Item = &Binding> {
+        self.bindings
+            .iter()
+            .filter(move |(a, _)| *a == action)
+            .map(|(_, b)| b)
+    }
+
+    /// Updates the action states based on the event.
+    pub fn handle<T>(&mut self, event: &Event<T>) {
+        match *event {
+            Event::KeyDown { code, vkey, .. } => {
+                self.set_held(Binding::ScanCode(code), true);
+                if let Some(vkey) = vkey {
+                    self.set_held(Binding::Key(vkey), true);
+                }
+            }
+            Event::KeyUp { code, vkey, .. } => {
+                self.set_held(Binding::ScanCode(code), false);
+                if let Some(vkey) = vkey {
+                    self.set_held(Binding::Key(vkey), false);
+                }
+            }
+            Event::MouseDown { ref button, .. } => {
+                self.set_held(Binding::Mouse(button.clone()), true)
+            }
+            Event::MouseUp { ref button, .. } => {
+                self.set_held(Binding::Mouse(button.clone()), false)
+            }
+            #[cfg(feature = "gilrs")]
+            Event::GamepadButtonDown { button, .. } => {
+                self.set_held(Binding::Gamepad(button), true)
+            }
+            #[cfg(feature = "gilrs")]
+            Event::GamepadButtonUp { button, .. } => self.set_held(Binding::Gamepad(button), false),
+            // Release events are not sent for inputs that are held when the window loses focus
+            Event::WindowBlur { .. } => self.release_all(),
+            _ => {}
+        }
+    }
+    /// Clears the just pressed and just released states. Call this once per frame after the
+    /// actions have been processed.
+    pub fn end_frame(&mut self) {
+        self.just_pressed.clear();
+        self.just_released.clear();
+    }
+    /// Releases all held inputs (ex. when opening a menu).
+    pub fn release_all(&mut self) {
+        self.held.clear();
+        let released: Vec<A> = self.pressed.drain().collect();
+        self.just_released.extend(released);
+    }
+
+    /// Indicates if any of the action's bindings are held down.
+    pub fn is_pressed(&self, action: A) -> bool {
+        self.pressed.contains(&action)
+    }
+    /// Indicates if the action was pressed since the last call to end_frame.
+    pub fn just_pressed(&self, action: A) -> bool {
+        self.just_pressed.contains(&action)
+    }
+    /// Indicates if the action was released since the last call to end_frame.
+    pub fn just_released(&self, action: A) -> bool {
+        self.just_released.contains(&action)
+    }
+
+    fn set_held(&mut self, binding: Binding, down: bool) {
+        let changed = if down {
+            self.held.insert(binding.clone())
+        } else {
+            self.held.remove(&binding)
+        };
+        if changed {
+            let actions: Vec<A> = self
+                .bindings
+                .iter()
+                .filter(|(_, b)| *b == binding)
+                .map(|(a, _)| *a)
+                .collect();
+            for action in actions {
+                self.update_action(action);
+            }
+        }
+    }
+    fn update_action(&mut self, action: A) {
+        let held = &self.held;
+        let down = self
+            .bindings
+            .iter()
+            .any(|(a, b)| *a == action && held.contains(b));
+        if down {
+            if self.pressed.insert(action) {
+                self.just_pressed.insert(action);
+            }
+        } else if self.pressed.remove(&action) {
+            self.just_released.insert(action);
+        }
+    }
+}
+
+impl<A: Copy + Eq + Hash> Default for ActionMap<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::Modifiers;
+    use glium::glutin::event::DeviceId;
+    use glium::glutin::window::WindowId;
+
+    #[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+    enum Action {
+        Jump,
+        Fire,
+    }
+
+    fn key(vkey: VirtualKeyCode, code: ScanCode, down: bool) -> Event<()> {
+        let win_id = unsafe { WindowId::dummy() };
+        let device_id = unsafe { DeviceId::dummy() };
+        let modifiers = Modifiers::default();
+        if down {
+            Event::KeyDown {
+                win_id,
+                device_id,
+                code,
+                vkey: Some(vkey),
+                modifiers,
+                repeat: false,
+            }
+        } else {
+            Event::KeyUp {
+                win_id,
+                device_id,
+                code,
+                vkey: Some(vkey),
+                modifiers,
+            }
+        }
+    }
+    fn mouse(button: MouseButton, down: bool) -> Event<()> {
+        let win_id = unsafe { WindowId::dummy() };
+        let device_id = unsafe { DeviceId::dummy() };
+        if down {
+            Event::MouseDown {
+                win_id,
+                device_id,
+                button,
+            }
+        } else {
+            Event::MouseUp {
+                win_id,
+                device_id,
+                button,
+            }
+        }
+    }
+
+    #[test]
+    fn pressed_while_any_binding_is_held() {
+        let mut actions = ActionMap::new();
+        actions.bind(Action::Jump, Binding::Key(VirtualKeyCode::Space));
+        actions.bind(Action::Jump, Binding::Mouse(MouseButton::Right));
+        actions.bind(Action::Fire, Binding::Mouse(MouseButton::Left));
+
+        actions.handle(&key(VirtualKeyCode::Space, 57, true));
+        actions.handle(&mouse(MouseButton::Right, true));
+        assert!(actions.is_pressed(Action::Jump));
+        assert!(actions.just_pressed(Action::Jump));
+        assert!(!actions.is_pressed(Action::Fire));
+
+        actions.end_frame();
+        actions.handle(&key(VirtualKeyCode::Space, 57, false));
+        assert!(actions.is_pressed(Action::Jump));
+        assert!(!actions.just_pressed(Action::Jump));
+        assert!(!actions.just_released(Action::Jump));
+
+        actions.handle(&mouse(MouseButton::Right, false));
+        assert!(!actions.is_pressed(Action::Jump));
+        assert!(actions.just_released(Action::Jump));
+    }
+
+    #[test]
+    fn scan_code_and_rebinding() {
+        let mut actions = ActionMap::new();
+        actions.bind(Action::Fire, Binding::ScanCode(17));
+        actions.bind(Action::Fire, Binding::ScanCode(17));
+        assert_eq!(actions.bindings(Action::Fire).count(), 1);
+
+        actions.handle(&key(VirtualKeyCode::Z, 17, true));
+        assert!(actions.is_pressed(Action::Fire));
+
+        // Removing the held binding releases the action
+        actions.unbind(Action::Fire, &Binding::ScanCode(17));
+        assert!(!actions.is_pressed(Action::Fire));
+        assert!(actions.just_released(Action::Fire));
+        assert_eq!(actions.bindings(Action::Fire).count(), 0);
+    }
+
+    #[test]
+    fn release_all_on_blur() {
+        let mut actions = ActionMap::new();
+        actions.bind(Action::Jump, Binding::Key(VirtualKeyCode::Space));
+        actions.handle(&key(VirtualKeyCode::Space, 57, true));
+        actions.end_frame();
+        actions.handle::<()>(&Event::WindowBlur {
+            win_id: unsafe { WindowId::dummy() },
+        });
+        assert!(!actions.is_pressed(Action::Jump));
+        assert!(actions.just_released(Action::Jump));
+    }
+
+    #[test]
+    fn binding_display() {
+        assert_eq!(Binding::Key(VirtualKeyCode::E).to_string(), "E");
+        assert_eq!(Binding::Key(VirtualKeyCode::Key1).to_string(), "Key1");
+        assert_eq!(Binding::ScanCode(17).to_string(), "Scan17");
+        assert_eq!(Binding::Mouse(MouseButton::Left).to_string(), "MouseLeft");
+        assert_eq!(Binding::Mouse(MouseButton::Other(8)).to_string(), "Mouse8");
+    }
+}
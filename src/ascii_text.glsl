@@ -9,13 +9,16 @@ uniform sampler2D ascii_texture;
 in vec2 position;
 in vec4 color;
 in vec2 texcoord;
+in float solid;
 
 smooth out vec4 v_color;
 smooth out vec2 v_texcoord;
+flat out float v_solid;
 
 void main() {
   v_color = color;
   v_texcoord = texcoord;
+  v_solid = solid;
   gl_Position = vec4(position, 0.0, 1.0) * matrix;
 }
 
@@ -23,11 +26,13 @@ void main() {
 
 smooth in vec4 v_color;
 smooth in vec2 v_texcoord;
+flat in float v_solid;
 
 out vec4 f_color;
 
 void main() {
-  float a = texture(ascii_texture, v_texcoord).a;
+  // Solid rectangles are drawn regardless of the texture
+  float a = max(texture(ascii_texture, v_texcoord).a, v_solid);
   if (a < 0.01) {
     discard;
   }
@@ -1,14 +1,24 @@
 use crate::ascii_text_img::ascii_raw_img;
+use crate::cp437::to_cp437;
+use crate::dynamic_mesh::DynamicMesh;
+use crate::screen_units::{check_hidpi, screen_ortho, screen_ortho_logical, Screen2d};
 use glium;
 use glium::backend::Facade;
 
+/// Size of a glyph (in logical units) when the scale is 1.0.
+const GLYPH_DIM: f32 = 8.0;
+/// Distance between glyphs and lines (in logical units) when the scale is 1.0.
+const GLYPH_ADVANCE: f32 = 9.0;
+
 #[derive(Clone, Copy, Debug)]
 struct AsciiVertex {
     position: [f32; 2],
     color: [f32; 4],
     texcoord: [f32; 2],
+    /// 1.0 for solid rectangles, which are filled in without using the texture.
+    solid: f32,
 }
-implement_vertex!(AsciiVertex, position, color, texcoord);
+implement_vertex!(AsciiVertex, position, color, texcoord, solid);
 
 /// ASCII text screen writer. This is useful for displaying simple messages on the screen using ASCII text.
 ///
@@ -28,11 +38,28 @@ implement_vertex!(AsciiVertex, position, color, texcoord);
 pub struct AsciiText {
     ascii_texture: glium::texture::Texture2d,
     ascii_program: glium::Program,
+    hidpi_override: Option<f32>,
 }
 impl AsciiText {
     /// Creates and initializes the ASCII texture and shaders.
     pub fn new(display: &glium::Display) -> Self {
         let raw = ascii_raw_img();
+        let texture =
+            glium::texture::Texture2d::new(display, raw).expect("Failed to load ASCII texture");
+        Self::with_texture(display, texture)
+    }
+
+    /// Creates the ASCII text writer using a custom glyph texture. The texture must use the same
+    /// layout as the built-in one: a square grid of 16x16 glyphs (in code page 437 order), where
+    /// transparent pixels are not drawn.
+    pub fn with_texture(display: &glium::Display, texture: glium::texture::Texture2d) -> Self {
+        let (w, h) = texture.dimensions();
+        assert!(
+            w == h && w > 0 && w % 16 == 0,
+            "ASCII texture must be square and a multiple of 16 pixels (a 16x16 grid of glyphs): {}x{}",
+            w,
+            h
+        );
 
         let program_src = include_str!("ascii_text.glsl").to_string();
         let program = glium::Program::from_source(
@@ -44,10 +71,30 @@ impl AsciiText {
         .expect("Failed to compile ASCII shader: ascii_text.glsl");
 
         Self {
-            ascii_texture: glium::texture::Texture2d::new(display, raw)
-                .expect("Failed to load ASCII texture"),
+            ascii_texture: texture,
             ascii_program: program,
+            hidpi_override: None,
+        }
+    }
+
+    /// Converts logical units to pixels using the given hidpi factor instead of the factor of the
+    /// window (ex. the factor passed to EventState::set_forced_hidpi, so that text is placed at the
+    /// positions reported by events). This also applies to TextBatch::flush. Use None to go back
+    /// to the window's factor.
+    pub fn set_hidpi_override(&mut self, factor: Option<f32>) {
+        if let Some(f) = factor {
+            check_hidpi(f);
         }
+        self.hidpi_override = factor;
+    }
+
+    /// Creates the ASCII text writer using custom glyph image data in RGBA format (see with_texture
+    /// for the expected layout).
+    pub fn from_rgba(display: &glium::Display, rgba: Vec<u8>, width: u32, height: u32) -> Self {
+        let raw = glium::texture::RawImage2d::from_raw_rgba(rgba, (width, height));
+        let texture = glium::texture::Texture2d::new(display, raw)
+            .expect("Failed to load custom ASCII texture");
+        Self::with_texture(display, texture)
     }
 
     /// Draws the specified text in white (see draw method for more information).
@@ -58,7 +105,7 @@ impl AsciiText {
         txt: &[u8],
         scale: f32,
         pos: [f32; 2],
-    ) {
+    ) -> [f32; 4] {
         self.draw(display, target, txt, scale, pos, [1.0, 1.0, 1.0, 1.0])
     }
     /// Draws the specified text in black (see draw method for more information).
@@ -69,16 +116,88 @@ impl AsciiText {
         txt: &[u8],
         scale: f32,
         pos: [f32; 2],
-    ) {
+    ) -> [f32; 4] {
         self.draw(display, target, txt, scale, pos, [0.0, 0.0, 0.0, 1.0])
     }
 
+    /// Calculates the width and height (in logical units) that the text will occupy when drawn at
+    /// the given scale. Each newline starts a new line, so the width is that of the longest line
+    /// and the height includes every line (including empty ones).
+    pub fn measure(&self, txt: &[u8], scale: f32) -> [f32; 2] {
+        self.measure_styled(txt, &TextStyle::new(scale, [1.0, 1.0, 1.0, 1.0]))
+    }
+    /// Same as measure, but uses the glyph spacing and line height from the style.
+    pub fn measure_styled(&self, txt: &[u8], style: &TextStyle) -> [f32; 2] {
+        text_size(txt, style)
+    }
+
+    /// Inserts line breaks into the text so that no line is wider than max_width (in logical units)
+    /// when drawn at the given scale. Lines are broken at spaces when possible, otherwise words
+    /// longer than the maximum width are broken between characters. Existing newlines are kept, as
+    /// are the spaces on each line, except at the points where a line is broken (those spaces are
+    /// removed).
+    pub fn wrap(&self, txt: &[u8], scale: f32, max_width: f32) -> Vec<u8> {
+        wrap_text(txt, scale, max_width)
+    }
+
+    /// Draws the specified text, wrapping it so that no line is wider than max_width (see the wrap
+    /// and draw methods for more information).
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_wrapped<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        scale: f32,
+        pos: [f32; 2],
+        color: [f32; 4],
+        max_width: f32,
+    ) -> [f32; 4] {
+        let wrapped = self.wrap(txt, scale, max_width);
+        self.draw(display, target, &wrapped, scale, pos, color)
+    }
+
+    /// Draws the specified text at a screen position (ex. the mouse position from the event state).
+    /// The position is converted using its logical units, so the text will be placed correctly on
+    /// HiDPI displays. See the draw method for more information.
+    pub fn draw_at<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        scale: f32,
+        pos: Screen2d,
+        color: [f32; 4],
+    ) -> [f32; 4] {
+        self.draw(display, target, txt, scale, pos.logical(), color)
+    }
+
+    /// Draws the specified UTF-8 text. Characters are converted to code page 437 (the character set
+    /// of the ASCII texture), and any that are not available are drawn as a small square (see the
+    /// to_cp437 function and the draw method for more information).
+    pub fn draw_str<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &str,
+        scale: f32,
+        pos: [f32; 2],
+        color: [f32; 4],
+    ) -> [f32; 4] {
+        self.draw(display, target, &to_cp437(txt), scale, pos, color)
+    }
+
     /// Draws the specified text to the screen.
     /// The scale and position should be specified in logical units (not physical pixels).
     ///
     /// The scale determines the size of the text (where 1.0 is 8 pixels high). The position is the
     /// location on the window from the upper-left corner. The color is in RGBA format (alpha
     /// blending is supported).
+    ///
+    /// Returns the area occupied by the text as [x, y, width, height] in logical units. The width
+    /// is the measured width (see the measure method), while the height includes the line spacing
+    /// below the last line, so the bottom of the area (y + height) is where the next line of text
+    /// should be drawn. All of the draw methods return the area in the same way.
     pub fn draw<DrawSurface: glium::Surface>(
         &self,
         display: &glium::Display,
@@ -87,78 +206,305 @@ impl AsciiText {
         scale: f32,
         pos: [f32; 2],
         color: [f32; 4],
-    ) {
-        let win_size = display.get_context().get_framebuffer_dimensions();
-
-        // Adjust for DPI factor
-        let hidpi_factor = display.gl_window().window().scale_factor() as f32;
-        let scale = scale * hidpi_factor;
-        let pos = [pos[0] * hidpi_factor, pos[1] * hidpi_factor];
-
-        let transform = {
-            // Scale and translate values
-            let (w, h) = (win_size.0 as f32, win_size.1 as f32);
-            let xs: f32 = 2.0 / w;
-            let xt: f32 = -w / 2.0;
-            let ys: f32 = -2.0 / h;
-            let yt: f32 = -h / 2.0;
-
-            [
-                [xs, 0.0, 0.0, xt * xs],
-                [0.0, ys, 0.0, yt * ys],
-                [0.0, 0.0, 1.0, 0.0],
-                [0.0, 0.0, 0.0, 1.0],
-            ]
-        };
+    ) -> [f32; 4] {
+        self.draw_transformed(
+            display,
+            target,
+            txt,
+            scale,
+            pos,
+            color,
+            logical_transform(display, self.hidpi_override),
+        )
+    }
 
-        let uniforms = uniform! {
-            matrix: transform,
-            ascii_texture: glium::uniforms::Sampler::new(&self.ascii_texture)
-                .magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest)
-                .minify_filter(glium::uniforms::MinifySamplerFilter::Nearest),
-        };
+    /// Same as draw, but for a target of the given size in pixels, such as a SimpleFrameBuffer
+    /// that renders to a texture. The window size is not used, and the scale and position are in
+    /// pixels of the target (the hidpi factor is not applied).
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_to<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        target_size: [u32; 2],
+        txt: &[u8],
+        scale: f32,
+        pos: [f32; 2],
+        color: [f32; 4],
+    ) -> [f32; 4] {
+        let transform = screen_ortho(target_size[0] as f32, target_size[1] as f32);
+        self.draw_transformed(display, target, txt, scale, pos, color, transform)
+    }
 
-        let mut draw_params: glium::draw_parameters::DrawParameters = Default::default();
-        draw_params.blend = glium::Blend::alpha_blending();
+    #[allow(clippy::too_many_arguments)]
+    fn draw_transformed<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        scale: f32,
+        pos: [f32; 2],
+        color: [f32; 4],
+        transform: [[f32; 4]; 4],
+    ) -> [f32; 4] {
+        let mut vertex_data = Vec::with_capacity(4 * txt.len());
+        let mut index_data = Vec::with_capacity(6 * txt.len());
+        add_text(&mut vertex_data, &mut index_data, txt, scale, pos, |_| {
+            color
+        });
+        self.draw_vertex_data_transformed(display, target, &vertex_data, &index_data, transform);
+        text_area(txt, &TextStyle::new(scale, color), pos)
+    }
+
+    /// Draws the specified text using the scale, spacing, and color from the style. See the draw
+    /// method for more information.
+    pub fn draw_styled<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        pos: [f32; 2],
+        style: &TextStyle,
+    ) -> [f32; 4] {
+        let mut vertex_data = Vec::with_capacity(4 * txt.len());
+        let mut index_data = Vec::with_capacity(6 * txt.len());
+        add_aligned_text(
+            &mut vertex_data,
+            &mut index_data,
+            txt,
+            style,
+            pos,
+            TextAlign::Left,
+            |_| style.color,
+        );
+        self.draw_vertex_data(display, target, &vertex_data, &index_data);
+        text_area(txt, style, pos)
+    }
+
+    /// Draws the specified text with the given alignment. The position is used as the anchor point,
+    /// for example, right-aligned text will end at the position and bottom-aligned text will have
+    /// its last line end at the position. Each line is aligned separately, and the returned area is
+    /// that of the widest line. See the draw method for more information.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_aligned<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        scale: f32,
+        pos: [f32; 2],
+        color: [f32; 4],
+        align: TextAlign,
+        valign: TextVAlign,
+    ) -> [f32; 4] {
+        let [width, height] = self.measure(txt, scale);
+        let pos = [pos[0], pos[1] - valign.offset_factor() * height];
 
         let mut vertex_data = Vec::with_capacity(4 * txt.len());
         let mut index_data = Vec::with_capacity(6 * txt.len());
+        add_aligned_text(
+            &mut vertex_data,
+            &mut index_data,
+            txt,
+            &TextStyle::new(scale, color),
+            pos,
+            align,
+            |_| color,
+        );
+        self.draw_vertex_data(display, target, &vertex_data, &index_data);
+
+        let left = [pos[0] - align.offset_factor() * width, pos[1]];
+        text_area(txt, &TextStyle::new(scale, color), left)
+    }
+
+    /// Draws the specified text on top of a filled rectangle. The rectangle covers the measured
+    /// size of the text plus the padding on each side (all in logical units). The background color
+    /// is in RGBA format, so a semi-transparent background can be used. The returned area is that of
+    /// the text (without the padding). See the draw method for more information.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_with_background<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        scale: f32,
+        pos: [f32; 2],
+        fg: [f32; 4],
+        bg: [f32; 4],
+        padding: f32,
+    ) -> [f32; 4] {
+        let size = self.measure(txt, scale);
+
+        let mut vertex_data = Vec::with_capacity(4 * txt.len() + 4);
+        let mut index_data = Vec::with_capacity(6 * txt.len() + 6);
+        add_rect(
+            &mut vertex_data,
+            &mut index_data,
+            [pos[0] - padding, pos[1] - padding],
+            [size[0] + 2.0 * padding, size[1] + 2.0 * padding],
+            bg,
+        );
+        add_text(&mut vertex_data, &mut index_data, txt, scale, pos, |_| fg);
+        self.draw_vertex_data(display, target, &vertex_data, &index_data);
+        text_area(txt, &TextStyle::new(scale, fg), pos)
+    }
 
-        let dim = scale * 8.0;
-        let next_char = scale * 9.0;
-        let mut x = pos[0];
-        let mut y = pos[1];
-        for glyph in txt {
-            if *glyph == b'\n' {
-                // Newline
-                x = pos[0];
-                y += next_char;
-            } else {
-                add_glyph(
-                    &mut vertex_data,
-                    &mut index_data,
-                    *glyph,
-                    dim,
-                    [x, y],
-                    color,
-                );
-                x += next_char;
+    /// Draws the specified text with a drop shadow, which makes it readable over any background.
+    /// The shadow is a copy of the text drawn in the shadow color, moved by the offset (in logical
+    /// units, ex. [1.0, 1.0] for a shadow below and to the right). The returned area is that of the
+    /// text (without the shadow). See the draw method for more information.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_shadowed<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        scale: f32,
+        pos: [f32; 2],
+        fg: [f32; 4],
+        shadow: [f32; 4],
+        offset: [f32; 2],
+    ) -> [f32; 4] {
+        let mut vertex_data = Vec::with_capacity(2 * 4 * txt.len());
+        let mut index_data = Vec::with_capacity(2 * 6 * txt.len());
+        let shadow_pos = [pos[0] + offset[0], pos[1] + offset[1]];
+        add_text(
+            &mut vertex_data,
+            &mut index_data,
+            txt,
+            scale,
+            shadow_pos,
+            |_| shadow,
+        );
+        add_text(&mut vertex_data, &mut index_data, txt, scale, pos, |_| fg);
+        self.draw_vertex_data(display, target, &vertex_data, &index_data);
+        text_area(txt, &TextStyle::new(scale, fg), pos)
+    }
+
+    /// Draws the specified text with an outline. The outline is made up of eight copies of the
+    /// text drawn in the outline color, each moved by the thickness (in logical units) in a
+    /// different direction. The returned area is that of the text (without the outline). See the
+    /// draw method for more information.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_outlined<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        scale: f32,
+        pos: [f32; 2],
+        fg: [f32; 4],
+        outline: [f32; 4],
+        thickness: f32,
+    ) -> [f32; 4] {
+        let mut vertex_data = Vec::with_capacity(9 * 4 * txt.len());
+        let mut index_data = Vec::with_capacity(9 * 6 * txt.len());
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                if dx != 0 || dy != 0 {
+                    let outline_pos = [
+                        pos[0] + dx as f32 * thickness,
+                        pos[1] + dy as f32 * thickness,
+                    ];
+                    add_text(
+                        &mut vertex_data,
+                        &mut index_data,
+                        txt,
+                        scale,
+                        outline_pos,
+                        |_| outline,
+                    );
+                }
             }
         }
+        add_text(&mut vertex_data, &mut index_data, txt, scale, pos, |_| fg);
+        self.draw_vertex_data(display, target, &vertex_data, &index_data);
+        text_area(txt, &TextStyle::new(scale, fg), pos)
+    }
 
-        let vertex_buffer = glium::VertexBuffer::immutable(display, &vertex_data)
+    /// Draws the specified text using a separate color for each character. The color for txt[i] is
+    /// colors[i]. If there are fewer colors than characters, then the last color is used for the
+    /// remaining characters (white is used if no colors are given). See the draw method for more
+    /// information.
+    pub fn draw_colored<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        txt: &[u8],
+        scale: f32,
+        pos: [f32; 2],
+        colors: &[[f32; 4]],
+    ) -> [f32; 4] {
+        let mut vertex_data = Vec::with_capacity(4 * txt.len());
+        let mut index_data = Vec::with_capacity(6 * txt.len());
+        add_text(&mut vertex_data, &mut index_data, txt, scale, pos, |idx| {
+            colors
+                .get(idx)
+                .or_else(|| colors.last())
+                .copied()
+                .unwrap_or([1.0, 1.0, 1.0, 1.0])
+        });
+        self.draw_vertex_data(display, target, &vertex_data, &index_data);
+        text_area(txt, &TextStyle::new(scale, [1.0, 1.0, 1.0, 1.0]), pos)
+    }
+
+    /// Draws vertex data specified in logical units.
+    fn draw_vertex_data<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        vertex_data: &[AsciiVertex],
+        index_data: &[u32],
+    ) {
+        let transform = logical_transform(display, self.hidpi_override);
+        self.draw_vertex_data_transformed(display, target, vertex_data, index_data, transform);
+    }
+
+    fn draw_vertex_data_transformed<DrawSurface: glium::Surface>(
+        &self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+        vertex_data: &[AsciiVertex],
+        index_data: &[u32],
+        transform: [[f32; 4]; 4],
+    ) {
+        let vertex_buffer = glium::VertexBuffer::immutable(display, vertex_data)
             .expect("Failed to create ASCII vertex buffer");
         let indices = glium::index::IndexBuffer::immutable(
             display,
             glium::index::PrimitiveType::TrianglesList,
-            &index_data,
+            index_data,
         )
         .expect("Failed to create ASCII index buffer");
 
+        self.draw_buffers(target, &vertex_buffer, &indices, transform);
+    }
+
+    fn draw_buffers<'a, 'b, DrawSurface, V, I>(
+        &self,
+        target: &mut DrawSurface,
+        vertices: V,
+        indices: I,
+        transform: [[f32; 4]; 4],
+    ) where
+        DrawSurface: glium::Surface,
+        V: glium::vertex::MultiVerticesSource<'b>,
+        I: Into<glium::index::IndicesSource<'a>>,
+    {
+        let uniforms = uniform! {
+            matrix: transform,
+            ascii_texture: glium::uniforms::Sampler::new(&self.ascii_texture)
+                .magnify_filter(glium::uniforms::MagnifySamplerFilter::Nearest)
+                .minify_filter(glium::uniforms::MinifySamplerFilter::Nearest),
+        };
+
+        let mut draw_params: glium::draw_parameters::DrawParameters = Default::default();
+        draw_params.blend = glium::Blend::alpha_blending();
+
         target
             .draw(
-                &vertex_buffer,
-                &indices,
+                vertices,
+                indices,
                 &self.ascii_program,
                 &uniforms,
                 &draw_params,
@@ -167,6 +513,352 @@ impl AsciiText {
     }
 }
 
+/// Size, spacing, and color of text. All values are in logical units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextStyle {
+    /// Size of the text (where 1.0 is 8 pixels high).
+    pub scale: f32,
+    /// Horizontal distance from the start of one glyph to the start of the next.
+    pub advance: f32,
+    /// Vertical distance from the top of one line to the top of the next.
+    pub line_height: f32,
+    /// Color in RGBA format.
+    pub color: [f32; 4],
+}
+impl TextStyle {
+    /// Creates a style with the default spacing for the given scale (9 pixels per glyph and line
+    /// when the scale is 1.0).
+    pub fn new(scale: f32, color: [f32; 4]) -> Self {
+        Self {
+            scale,
+            advance: scale * GLYPH_ADVANCE,
+            line_height: scale * GLYPH_ADVANCE,
+            color,
+        }
+    }
+    pub fn with_advance(mut self, advance: f32) -> Self {
+        self.advance = advance;
+        self
+    }
+    pub fn with_line_height(mut self, line_height: f32) -> Self {
+        self.line_height = line_height;
+        self
+    }
+    fn glyph_dim(&self) -> f32 {
+        self.scale * GLYPH_DIM
+    }
+}
+
+/// Horizontal alignment of text relative to its position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextAlign {
+    Left,
+    Center,
+    Right,
+}
+impl TextAlign {
+    fn offset_factor(self) -> f32 {
+        match self {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => 0.5,
+            TextAlign::Right => 1.0,
+        }
+    }
+}
+
+/// Vertical alignment of text relative to its position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextVAlign {
+    Top,
+    Middle,
+    Bottom,
+}
+impl TextVAlign {
+    fn offset_factor(self) -> f32 {
+        match self {
+            TextVAlign::Top => 0.0,
+            TextVAlign::Middle => 0.5,
+            TextVAlign::Bottom => 1.0,
+        }
+    }
+}
+
+/// Collects text from multiple calls so that it can be drawn with a single draw call, which makes
+/// this well suited for drawing many labels every frame. The batch does not own the glyph texture,
+/// so the same batch can be flushed with any AsciiText.
+///
+/// # Examples
+///
+/// ```
+/// let ascii_text = AsciiText::new(&display);
+/// let mut batch = TextBatch::new();
+/// loop {
+///   let target = display.draw();
+///
+///   batch.push(b"Health: 100", 1.5, [10.0, 10.0], [1.0, 1.0, 1.0, 1.0]);
+///   batch.push(b"Score: 1234", 1.5, [10.0, 30.0], [1.0, 1.0, 0.0, 1.0]);
+///   batch.flush(&ascii_text, &display, &mut target);
+///
+///   target.finish().unwrap();
+/// }
+/// ```
+pub struct TextBatch {
+    mesh: DynamicMesh<AsciiVertex>,
+}
+impl TextBatch {
+    pub fn new() -> Self {
+        Self {
+            mesh: DynamicMesh::new("ASCII"),
+        }
+    }
+
+    /// Adds the text to the batch (see AsciiText::draw for a description of the parameters and the
+    /// returned area).
+    pub fn push(&mut self, txt: &[u8], scale: f32, pos: [f32; 2], color: [f32; 4]) -> [f32; 4] {
+        let (vertex_data, index_data) = self.mesh.data_mut();
+        add_text(vertex_data, index_data, txt, scale, pos, |_| color);
+        text_area(txt, &TextStyle::new(scale, color), pos)
+    }
+
+    /// Indicates if there is no text waiting to be drawn.
+    pub fn is_empty(&self) -> bool {
+        self.mesh.is_empty()
+    }
+
+    /// Removes all text from the batch without drawing it.
+    pub fn clear(&mut self) {
+        self.mesh.clear();
+    }
+
+    /// Draws all of the text in the batch using a single draw call, then clears the batch.
+    pub fn flush<DrawSurface: glium::Surface>(
+        &mut self,
+        ascii_text: &AsciiText,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        let (vertex_buffer, indices) = self.mesh.upload(display);
+        ascii_text.draw_buffers(
+            target,
+            vertex_buffer,
+            indices,
+            logical_transform(display, ascii_text.hidpi_override),
+        );
+
+        self.clear();
+    }
+}
+impl Default for TextBatch {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Transform from logical units to OpenGL coordinates for the display's window. The window's hidpi
+/// factor is used unless an override is given.
+pub(crate) fn logical_transform(
+    display: &glium::Display,
+    hidpi_override: Option<f32>,
+) -> [[f32; 4]; 4] {
+    let (w, h) = display.get_context().get_framebuffer_dimensions();
+    let hidpi_factor =
+        hidpi_override.unwrap_or_else(|| display.gl_window().window().scale_factor() as f32);
+    framebuffer_transform([w, h], hidpi_factor)
+}
+
+/// Transform from logical units to OpenGL coordinates for a framebuffer of the given size (in
+/// physical pixels).
+pub(crate) fn framebuffer_transform(size: [u32; 2], hidpi_factor: f32) -> [[f32; 4]; 4] {
+    screen_ortho_logical(Screen2d::from_physical_u32(size, hidpi_factor))
+}
+
+/// Size of the text (see AsciiText::measure).
+fn text_size(txt: &[u8], style: &TextStyle) -> [f32; 2] {
+    let mut max_len = 0;
+    let mut line_count = 0;
+    for line in txt.split(|ch| *ch == b'\n') {
+        max_len = max_len.max(line.len());
+        line_count += 1;
+    }
+
+    let w = line_width(max_len, style);
+    let h = (line_count - 1) as f32 * style.line_height + style.glyph_dim();
+    [w, h]
+}
+
+/// Area occupied by the text when drawn at the position (see AsciiText::draw). Unlike text_size,
+/// the height includes the line spacing below the last line.
+fn text_area(txt: &[u8], style: &TextStyle, pos: [f32; 2]) -> [f32; 4] {
+    let line_count = txt.split(|ch| *ch == b'\n').count();
+    let width = text_size(txt, style)[0];
+    [pos[0], pos[1], width, line_count as f32 * style.line_height]
+}
+
+/// Width of a line with the given number of characters.
+fn line_width(len: usize, style: &TextStyle) -> f32 {
+    if len > 0 {
+        (len - 1) as f32 * style.advance + style.glyph_dim()
+    } else {
+        0.0
+    }
+}
+
+/// Adds the glyphs for the text (the color for each glyph is looked up by its index in txt).
+fn add_text(
+    vertex_data: &mut Vec<AsciiVertex>,
+    index_data: &mut Vec<u32>,
+    txt: &[u8],
+    scale: f32,
+    pos: [f32; 2],
+    color: impl Fn(usize) -> [f32; 4],
+) {
+    add_aligned_text(
+        vertex_data,
+        index_data,
+        txt,
+        &TextStyle::new(scale, [1.0, 1.0, 1.0, 1.0]),
+        pos,
+        TextAlign::Left,
+        color,
+    )
+}
+
+fn add_aligned_text(
+    vertex_data: &mut Vec<AsciiVertex>,
+    index_data: &mut Vec<u32>,
+    txt: &[u8],
+    style: &TextStyle,
+    pos: [f32; 2],
+    align: TextAlign,
+    color: impl Fn(usize) -> [f32; 4],
+) {
+    let dim = style.glyph_dim();
+    let line_start = |start: usize| {
+        let len = txt[start..]
+            .iter()
+            .position(|ch| *ch == b'\n')
+            .unwrap_or(txt.len() - start);
+        pos[0] - align.offset_factor() * line_width(len, style)
+    };
+
+    let mut x = line_start(0);
+    let mut y = pos[1];
+    for (idx, glyph) in txt.iter().enumerate() {
+        if *glyph == b'\n' {
+            // Newline
+            x = line_start(idx + 1);
+            y += style.line_height;
+        } else {
+            add_glyph(vertex_data, index_data, *glyph, dim, [x, y], color(idx));
+            x += style.advance;
+        }
+    }
+}
+
+fn wrap_text(txt: &[u8], scale: f32, max_width: f32) -> Vec<u8> {
+    let dim = scale * GLYPH_DIM;
+    let next_char = scale * GLYPH_ADVANCE;
+    let max_chars = if max_width > dim {
+        ((max_width - dim) / next_char) as usize + 1
+    } else {
+        1
+    };
+
+    let mut result = Vec::with_capacity(txt.len());
+    for (idx, line) in txt.split(|ch| *ch == b'\n').enumerate() {
+        if idx > 0 {
+            result.push(b'\n');
+        }
+        wrap_line(&mut result, line, max_chars);
+    }
+    result
+}
+
+fn wrap_line(result: &mut Vec<u8>, line: &[u8], max_chars: usize) {
+    let mut len = 0;
+    let mut rest = line;
+    while !rest.is_empty() {
+        // Each step takes the spaces in front of a word and then the word itself
+        let gap = rest.iter().take_while(|ch| **ch == b' ').count();
+        let (spaces, tail) = rest.split_at(gap);
+        let word_len = tail.iter().take_while(|ch| **ch != b' ').count();
+        let (mut word, tail) = tail.split_at(word_len);
+        rest = tail;
+
+        if len + spaces.len() + word.len() <= max_chars {
+            result.extend_from_slice(spaces);
+            len += spaces.len();
+        } else if len > 0 && !word.is_empty() {
+            // Break the line here, dropping the spaces
+            result.push(b'\n');
+            len = 0;
+        }
+        while len + word.len() > max_chars {
+            // Word does not fit on a line by itself, break it up
+            let (head, tail) = word.split_at(max_chars - len);
+            result.extend_from_slice(head);
+            result.push(b'\n');
+            len = 0;
+            word = tail;
+        }
+        result.extend_from_slice(word);
+        len += word.len();
+    }
+}
+
+/// Adds a filled rectangle (the texture is not used, so this works with any glyph texture).
+fn add_rect(
+    vertex_data: &mut Vec<AsciiVertex>,
+    index_data: &mut Vec<u32>,
+    pos: [f32; 2],
+    size: [f32; 2],
+    color: [f32; 4],
+) {
+    let idx = vertex_data.len() as u32;
+
+    let x = pos[0];
+    let y = pos[1];
+
+    let texcoord = [0.0, 0.0];
+
+    vertex_data.push(AsciiVertex {
+        position: [x, y],
+        texcoord,
+        color,
+        solid: 1.0,
+    });
+    vertex_data.push(AsciiVertex {
+        position: [x + size[0], y],
+        texcoord,
+        color,
+        solid: 1.0,
+    });
+    vertex_data.push(AsciiVertex {
+        position: [x + size[0], y + size[1]],
+        texcoord,
+        color,
+        solid: 1.0,
+    });
+    vertex_data.push(AsciiVertex {
+        position: [x, y + size[1]],
+        texcoord,
+        color,
+        solid: 1.0,
+    });
+
+    index_data.push(idx);
+    index_data.push(idx + 1);
+    index_data.push(idx + 3);
+
+    index_data.push(idx + 1);
+    index_data.push(idx + 2);
+    index_data.push(idx + 3);
+}
+
 fn add_glyph(
     vertex_data: &mut Vec<AsciiVertex>,
     index_data: &mut Vec<u32>,
@@ -183,30 +875,35 @@ fn add_glyph(
     let tx = glyph % 16;
     let ty = glyph / 16;
 
-    let tx = 8.0 * (tx as u32 as f32) / 128.0;
-    let ty = 8.0 * (ty as u32 as f32) / 128.0;
+    // Texture coordinates within the 16x16 grid of glyphs
+    let tx = (tx as u32 as f32) / 16.0;
+    let ty = (ty as u32 as f32) / 16.0;
 
-    let tdim = 8.0 / 128.0;
+    let tdim = 1.0 / 16.0;
 
     vertex_data.push(AsciiVertex {
         position: [x, y],
         texcoord: [tx, ty],
         color,
+        solid: 0.0,
     });
     vertex_data.push(AsciiVertex {
         position: [x + dim, y],
         texcoord: [tx + tdim, ty],
         color,
+        solid: 0.0,
     });
     vertex_data.push(AsciiVertex {
         position: [x + dim, y + dim],
         texcoord: [tx + tdim, ty + tdim],
         color,
+        solid: 0.0,
     });
     vertex_data.push(AsciiVertex {
         position: [x, y + dim],
         texcoord: [tx, ty + tdim],
         color,
+        solid: 0.0,
     });
 
     index_data.push(idx + 0);
@@ -217,3 +914,115 @@ fn add_glyph(
     index_data.push(idx + 2);
     index_data.push(idx + 3);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bounding box of the glyphs that draw would place, as [x, y, width, height].
+    fn glyph_bounds(txt: &[u8], scale: f32, pos: [f32; 2]) -> [f32; 4] {
+        let mut vertex_data = Vec::new();
+        let mut index_data = Vec::new();
+        add_text(&mut vertex_data, &mut index_data, txt, scale, pos, |_| {
+            [1.0, 1.0, 1.0, 1.0]
+        });
+        let (mut x0, mut y0) = (f32::MAX, f32::MAX);
+        let (mut x1, mut y1) = (f32::MIN, f32::MIN);
+        for v in &vertex_data {
+            x0 = x0.min(v.position[0]);
+            y0 = y0.min(v.position[1]);
+            x1 = x1.max(v.position[0]);
+            y1 = y1.max(v.position[1]);
+        }
+        [x0, y0, x1 - x0, y1 - y0]
+    }
+
+    #[test]
+    fn measure_multi_line() {
+        let style = TextStyle::new(2.0, [1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(text_size(b"abc", &style), [2.0 * 18.0 + 16.0, 16.0]);
+        assert_eq!(
+            text_size(b"ab\nabcd\nx", &style),
+            [3.0 * 18.0 + 16.0, 2.0 * 18.0 + 16.0]
+        );
+        // Empty lines still take up space
+        assert_eq!(text_size(b"ab\n", &style), [18.0 + 16.0, 18.0 + 16.0]);
+        assert_eq!(text_size(b"", &style), [0.0, 16.0]);
+    }
+
+    #[test]
+    fn measure_matches_drawn_glyphs() {
+        // The first and last lines are not empty, so the glyphs cover the whole measured area
+        for txt in &[&b"abc"[..], b"ab\nabcd\nx", b"abcd\n\na"] {
+            let [x, y, w, h] = glyph_bounds(txt, 1.5, [10.0, 20.0]);
+            assert_eq!([x, y], [10.0, 20.0]);
+            assert_eq!([w, h], text_size(txt, &TextStyle::new(1.5, [1.0; 4])));
+        }
+    }
+
+    #[test]
+    fn drawn_area_stacks_lines() {
+        let style = TextStyle::new(2.0, [1.0, 1.0, 1.0, 1.0]);
+        let first = text_area(b"ab\nabcd", &style, [10.0, 20.0]);
+        assert_eq!(first, [10.0, 20.0, 3.0 * 18.0 + 16.0, 2.0 * 18.0]);
+        // Drawing at the bottom of the area continues with the usual line spacing
+        let second = glyph_bounds(b"x", 2.0, [10.0, first[1] + first[3]]);
+        let both = glyph_bounds(b"ab\nabcd\nx", 2.0, [10.0, 20.0]);
+        assert_eq!(second[1] + second[3], both[1] + both[3]);
+        // Trailing newlines add a line
+        assert_eq!(text_area(b"ab\n", &style, [0.0, 0.0])[3], 2.0 * 18.0);
+    }
+
+    /// Width of n glyphs drawn at scale 1.0.
+    fn chars_width(n: usize) -> f32 {
+        GLYPH_DIM + (n - 1) as f32 * GLYPH_ADVANCE
+    }
+
+    #[test]
+    fn wrap_breaks_at_spaces() {
+        assert_eq!(wrap_text(b"aaa bb cc", 1.0, chars_width(6)), b"aaa bb\ncc");
+        // Spaces are only removed where the line is broken
+        assert_eq!(wrap_text(b"a  b", 1.0, chars_width(6)), b"a  b");
+        assert_eq!(wrap_text(b"aa   bb  c", 1.0, chars_width(3)), b"aa\nbb\nc");
+        assert_eq!(
+            wrap_text(b"  indented", 1.0, chars_width(10)),
+            b"  indented"
+        );
+    }
+
+    #[test]
+    fn wrap_keeps_newlines() {
+        assert_eq!(wrap_text(b"ab\ncd", 1.0, 1000.0), b"ab\ncd");
+        assert_eq!(
+            wrap_text(b"aaaa bb\ncc", 1.0, chars_width(5)),
+            b"aaaa\nbb\ncc"
+        );
+        assert_eq!(wrap_text(b"a\n\nb", 1.0, chars_width(5)), b"a\n\nb");
+    }
+
+    #[test]
+    fn wrap_breaks_long_words() {
+        assert_eq!(
+            wrap_text(b"abcdefghij", 1.0, chars_width(4)),
+            b"abcd\nefgh\nij"
+        );
+        assert_eq!(
+            wrap_text(b"ab cdefgh", 1.0, chars_width(4)),
+            b"ab\ncdef\ngh"
+        );
+        // Less room than a single glyph still places one character per line
+        assert_eq!(wrap_text(b"ab c", 2.0, 4.0), b"a\nb\nc");
+    }
+
+    #[test]
+    fn wrapped_text_fits() {
+        let txt = b"The quick brown  fox jumps over the lazy dog\nsupercalifragilistic";
+        for &scale in &[1.0, 1.5, 3.0] {
+            for &max_width in &[40.0, 75.5, 120.0, 333.0] {
+                let wrapped = wrap_text(txt, scale, max_width);
+                let style = TextStyle::new(scale, [1.0; 4]);
+                assert!(text_size(&wrapped, &style)[0] <= max_width);
+            }
+        }
+    }
+}
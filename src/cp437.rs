@@ -0,0 +1,69 @@
+/// Glyph used for characters that are not in the code page 437 character set (a small square).
+const MISSING_GLYPH: u8 = 254;
+
+/// Converts text to code page 437 (the character set used by the ASCII texture). ASCII characters
+/// are unchanged, other characters are mapped to their code page 437 equivalent when one exists and
+/// to a small square otherwise.
+pub fn to_cp437(txt: &str) -> Vec<u8> {
+    txt.chars().map(char_to_cp437).collect()
+}
+
+fn char_to_cp437(ch: char) -> u8 {
+    if (ch as u32) < 128 {
+        return ch as u8;
+    }
+    if ch == '\u{2302}' {
+        return 127;
+    }
+    if let Some(idx) = CP437_HIGH.iter().position(|c| *c == ch) {
+        return (128 + idx) as u8;
+    }
+    if let Some(idx) = CP437_LOW.iter().position(|c| *c == ch) {
+        return (1 + idx) as u8;
+    }
+    MISSING_GLYPH
+}
+
+/// Characters 128 to 255.
+const CP437_HIGH: [char; 128] = [
+    'Ç', 'ü', 'é', 'â', 'ä', 'à', 'å', 'ç', 'ê', 'ë', 'è', 'ï', 'î', 'ì', 'Ä', 'Å', 'É', 'æ', 'Æ',
+    'ô', 'ö', 'ò', 'û', 'ù', 'ÿ', 'Ö', 'Ü', '¢', '£', '¥', '₧', 'ƒ', 'á', 'í', 'ó', 'ú', 'ñ', 'Ñ',
+    'ª', 'º', '¿', '⌐', '¬', '½', '¼', '¡', '«', '»', '░', '▒', '▓', '│', '┤', '╡', '╢', '╖', '╕',
+    '╣', '║', '╗', '╝', '╜', '╛', '┐', '└', '┴', '┬', '├', '─', '┼', '╞', '╟', '╚', '╔', '╩', '╦',
+    '╠', '═', '╬', '╧', '╨', '╤', '╥', '╙', '╘', '╒', '╓', '╫', '╪', '┘', '┌', '█', '▄', '▌', '▐',
+    '▀', 'α', 'ß', 'Γ', 'π', 'Σ', 'σ', 'µ', 'τ', 'Φ', 'Θ', 'Ω', 'δ', '∞', 'φ', 'ε', '∩', '≡', '±',
+    '≥', '≤', '⌠', '⌡', '÷', '≈', '°', '∙', '·', '√', 'ⁿ', '²', '■', '\u{00A0}',
+];
+
+/// Characters 1 to 31 (the graphical versions of the control characters).
+const CP437_LOW: [char; 31] = [
+    '☺', '☻', '♥', '♦', '♣', '♠', '•', '◘', '○', '◙', '♂', '♀', '♪', '♫', '☼', '►', '◄', '↕', '‼',
+    '¶', '§', '▬', '↨', '↑', '↓', '→', '←', '∟', '↔', '▲', '▼',
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_is_unchanged() {
+        assert_eq!(to_cp437("Hello, 123!\n"), b"Hello, 123!\n".to_vec());
+    }
+
+    #[test]
+    fn maps_high_and_low_characters() {
+        assert_eq!(to_cp437("é½█☺▼⌂"), vec![130, 171, 219, 1, 31, 127]);
+        assert_eq!(to_cp437("\u{00A0}"), vec![255]);
+        assert_eq!(to_cp437("日本"), vec![MISSING_GLYPH, MISSING_GLYPH]);
+    }
+
+    #[test]
+    fn every_glyph_maps_back_to_itself() {
+        for (idx, ch) in CP437_HIGH.iter().enumerate() {
+            assert_eq!(char_to_cp437(*ch), (128 + idx) as u8, "{}", ch);
+        }
+        for (idx, ch) in CP437_LOW.iter().enumerate() {
+            assert_eq!(char_to_cp437(*ch), (1 + idx) as u8, "{}", ch);
+        }
+    }
+}
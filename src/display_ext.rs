@@ -0,0 +1,232 @@
+use crate::screen_units::Screen2d;
+use crate::window::FullscreenMode;
+use glium::glutin::error::ExternalError;
+use glium::glutin::monitor::MonitorHandle;
+use glium::glutin::window::{BadIcon, Icon};
+use glium::{ReadError, SwapBuffersError};
+use std::ops::{Deref, DerefMut};
+
+/// Additional window operations for the display. These are available within the Window::run
+/// callback, which only has access to the display.
+///
+/// # Examples
+///
+/// ```
+/// use glu::DisplayExt;
+///
+/// // Toggle borderless fullscreen when F11 is pressed
+/// display.set_fullscreen(glu::FullscreenMode::Borderless(None));
+/// ```
+pub trait DisplayExt {
+    /// Changes the fullscreen mode of the window.
+    fn set_fullscreen(&self, mode: FullscreenMode);
+    /// Indicates if the window is currently fullscreen (borderless or exclusive).
+    fn is_fullscreen(&self) -> bool;
+    /// Lists all monitors available to the window. The video modes of each monitor can be used to
+    /// select a resolution for exclusive fullscreen.
+    fn available_monitors(&self) -> Vec<MonitorHandle>;
+
+    /// Size of the window's framebuffer in physical pixels. This is queried from the context, so it
+    /// reflects the latest size (including after a WindowResize event).
+    fn framebuffer_size(&self) -> [u32; 2];
+    /// Size of the window's framebuffer using the window's current hidpi factor.
+    fn framebuffer_size_screen(&self) -> Screen2d;
+    /// Width divided by height of the framebuffer (ex. for a perspective projection). This is 1.0
+    /// while the window is minimized (when the size is zero).
+    fn aspect_ratio(&self) -> f32;
+
+    /// Starts drawing a new frame. Unlike glium's draw, the frame is finished (presented) when the
+    /// returned guard is dropped, so it cannot be forgotten. See FrameGuard for more information.
+    fn draw_frame(&self) -> FrameGuard;
+
+    /// Requests that the window be redrawn, which results in an Event::Redraw being delivered to
+    /// the run callback. When combined with WindowState::Wait, this allows the window to only be
+    /// repainted when something has changed. Multiple requests made before the redraw occurs are
+    /// combined into a single redraw.
+    fn request_redraw(&self);
+
+    /// Shows or hides the cursor while it is over the window.
+    fn set_cursor_visible(&self, visible: bool);
+    /// Confines the cursor to the window (ex. for first-person camera controls). While grabbed,
+    /// use the raw Event::MouseMotion deltas rather than the MouseMove position. Grabbing the
+    /// cursor is not supported on every platform, in which case an error is returned.
+    fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError>;
+    /// Moves the cursor to the center of the window.
+    fn center_cursor(&self) -> Result<(), ExternalError>;
+
+    /// Sets the location of the IME candidate box (ex. at the text cursor of the focused text
+    /// field), relative to the top left of the window. This is not supported on iOS, Android or
+    /// the web. There is no method to enable the IME, since winit 0.24 always enables it where it
+    /// is supported (see Event::ImePreedit).
+    fn set_ime_position(&self, pos: Screen2d);
+
+    /// Replaces the window icon (ex. to show a status or to animate the icon by swapping frames).
+    /// The data is RGBA with the top-left pixel first. On Windows this is also used for the
+    /// taskbar, and on X11 for the window switcher. This does nothing on macOS (which uses the
+    /// application bundle icon), Wayland, iOS, Android and the web.
+    ///
+    /// Taskbar progress is not available, since winit does not provide access to the taskbar. On
+    /// Windows, it can be shown with ITaskbarList3 using the window handle from
+    /// Window::raw_window_handle (requires the raw-window-handle feature).
+    fn set_icon_rgba(&self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon>;
+
+    /// Reads the contents of the front buffer (the most recently presented frame). The result is
+    /// RGBA data with the top-left pixel first, along with the dimensions in physical pixels.
+    fn capture_frame(&self) -> Result<(Vec<u8>, [u32; 2]), ReadError>;
+    /// Same as capture_frame, but returns an image (requires the `image` feature). This can be
+    /// saved as a screenshot or compared against a reference image in tests.
+    #[cfg(feature = "image")]
+    fn capture_image(&self) -> Result<image::RgbaImage, ReadError>;
+
+    /// Text currently on the clipboard (requires the `arboard` feature). Returns None if the
+    /// clipboard is empty, does not contain text, or cannot be accessed.
+    #[cfg(feature = "arboard")]
+    fn clipboard_get(&self) -> Option<String>;
+    /// Places the text on the clipboard (requires the `arboard` feature). This does nothing if the
+    /// clipboard cannot be accessed.
+    #[cfg(feature = "arboard")]
+    fn clipboard_set(&self, text: &str);
+}
+impl DisplayExt for glium::Display {
+    fn set_fullscreen(&self, mode: FullscreenMode) {
+        self.gl_window().window().set_fullscreen(mode.into_glutin());
+    }
+    fn is_fullscreen(&self) -> bool {
+        self.gl_window().window().fullscreen().is_some()
+    }
+    fn framebuffer_size(&self) -> [u32; 2] {
+        use glium::backend::Facade;
+        let (w, h) = self.get_context().get_framebuffer_dimensions();
+        [w, h]
+    }
+    fn framebuffer_size_screen(&self) -> Screen2d {
+        let hidpi_factor = self.gl_window().window().scale_factor() as f32;
+        Screen2d::from_physical_u32(self.framebuffer_size(), hidpi_factor)
+    }
+    fn aspect_ratio(&self) -> f32 {
+        let [w, h] = self.framebuffer_size();
+        if w == 0 || h == 0 {
+            1.0
+        } else {
+            w as f32 / h as f32
+        }
+    }
+    fn available_monitors(&self) -> Vec<MonitorHandle> {
+        self.gl_window().window().available_monitors().collect()
+    }
+
+    fn draw_frame(&self) -> FrameGuard {
+        FrameGuard {
+            frame: Some(self.draw()),
+        }
+    }
+    fn request_redraw(&self) {
+        self.gl_window().window().request_redraw();
+    }
+
+    fn set_cursor_visible(&self, visible: bool) {
+        self.gl_window().window().set_cursor_visible(visible);
+    }
+    fn set_cursor_grab(&self, grab: bool) -> Result<(), ExternalError> {
+        self.gl_window().window().set_cursor_grab(grab)
+    }
+    fn center_cursor(&self) -> Result<(), ExternalError> {
+        let gl_window = self.gl_window();
+        let window = gl_window.window();
+        let size = window.inner_size();
+        let center = glium::glutin::dpi::PhysicalPosition::new(size.width / 2, size.height / 2);
+        window.set_cursor_position(center)
+    }
+
+    fn set_ime_position(&self, pos: Screen2d) {
+        let [x, y] = pos.logical();
+        let pos = glium::glutin::dpi::LogicalPosition::new(x as f64, y as f64);
+        self.gl_window().window().set_ime_position(pos);
+    }
+
+    fn set_icon_rgba(&self, rgba: Vec<u8>, width: u32, height: u32) -> Result<(), BadIcon> {
+        let icon = Icon::from_rgba(rgba, width, height)?;
+        self.gl_window().window().set_window_icon(Some(icon));
+        Ok(())
+    }
+
+    fn capture_frame(&self) -> Result<(Vec<u8>, [u32; 2]), ReadError> {
+        let img: glium::texture::RawImage2d<u8> = self.read_front_buffer()?;
+        let row_len = img.width as usize * 4;
+        let mut data = Vec::with_capacity(img.data.len());
+        if row_len > 0 {
+            // OpenGL returns the bottom row first
+            for row in img.data.chunks_exact(row_len).rev() {
+                data.extend_from_slice(row);
+            }
+        }
+        Ok((data, [img.width, img.height]))
+    }
+    #[cfg(feature = "image")]
+    fn capture_image(&self) -> Result<image::RgbaImage, ReadError> {
+        let (data, [w, h]) = self.capture_frame()?;
+        Ok(image::RgbaImage::from_raw(w, h, data).expect("Frame data does not match dimensions"))
+    }
+
+    #[cfg(feature = "arboard")]
+    fn clipboard_get(&self) -> Option<String> {
+        let mut clipboard = arboard::Clipboard::new().ok()?;
+        clipboard.get_text().ok().filter(|text| !text.is_empty())
+    }
+    #[cfg(feature = "arboard")]
+    fn clipboard_set(&self, text: &str) {
+        if let Ok(mut clipboard) = arboard::Clipboard::new() {
+            let _ = clipboard.set_text(text.to_string());
+        }
+    }
+}
+
+/// Frame that is finished automatically when it is dropped (see DisplayExt::draw_frame). The frame
+/// is accessible through Deref, so its methods can be called directly on the guard. Use
+/// `&mut *guard` where a glium::Surface is expected (ex. AsciiText::draw).
+///
+/// Dropping the guard panics if the buffers could not be swapped (unless the thread is already
+/// panicking). Call finish to handle the error instead.
+///
+/// # Examples
+///
+/// ```
+/// use glu::DisplayExt;
+/// use glium::Surface;
+///
+/// let mut target = display.draw_frame();
+/// target.clear_color(0.0, 0.0, 0.0, 1.0);
+/// ascii_text.draw_white(&display, &mut *target, b"Hello", 2.0, [10.0, 10.0]);
+/// // The frame is presented here
+/// ```
+pub struct FrameGuard {
+    frame: Option<glium::Frame>,
+}
+impl FrameGuard {
+    /// Finishes the frame now, returning any error that occurs while swapping the buffers.
+    pub fn finish(mut self) -> Result<(), SwapBuffersError> {
+        self.frame.take().expect("Frame already finished").finish()
+    }
+}
+impl Deref for FrameGuard {
+    type Target = glium::Frame;
+    fn deref(&self) -> &glium::Frame {
+        self.frame.as_ref().expect("Frame already finished")
+    }
+}
+impl DerefMut for FrameGuard {
+    fn deref_mut(&mut self) -> &mut glium::Frame {
+        self.frame.as_mut().expect("Frame already finished")
+    }
+}
+impl Drop for FrameGuard {
+    fn drop(&mut self) {
+        if let Some(frame) = self.frame.take() {
+            if let Err(e) = frame.finish() {
+                if !std::thread::panicking() {
+                    panic!("Failed to finish frame: {:?}", e);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,96 @@
+/// Quads that are collected on the CPU and then drawn with a single draw call (used by
+/// ShapeRenderer, SpriteBatch and TextBatch). The GPU buffers are reused for each upload, and are
+/// only replaced when the data no longer fits.
+pub(crate) struct DynamicMesh<V: glium::Vertex> {
+    name: &'static str,
+    vertex_data: Vec<V>,
+    index_data: Vec<u32>,
+    vertex_buffer: Option<glium::VertexBuffer<V>>,
+    index_buffer: Option<glium::IndexBuffer<u32>>,
+}
+impl<V: glium::Vertex> DynamicMesh<V> {
+    /// Creates an empty mesh. The name is used in the error messages (ex. "sprite").
+    pub(crate) fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            vertex_data: Vec::new(),
+            index_data: Vec::new(),
+            vertex_buffer: None,
+            index_buffer: None,
+        }
+    }
+
+    /// Adds a quad with the corners in order around the edge.
+    pub(crate) fn push_quad(&mut self, corners: [V; 4]) {
+        let idx = self.vertex_data.len() as u32;
+        self.vertex_data.extend_from_slice(&corners);
+
+        self.index_data.push(idx);
+        self.index_data.push(idx + 1);
+        self.index_data.push(idx + 3);
+
+        self.index_data.push(idx + 1);
+        self.index_data.push(idx + 2);
+        self.index_data.push(idx + 3);
+    }
+    /// Vertex and index data that has not been uploaded yet (ex. for add_text).
+    pub(crate) fn data_mut(&mut self) -> (&mut Vec<V>, &mut Vec<u32>) {
+        (&mut self.vertex_data, &mut self.index_data)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.index_data.is_empty()
+    }
+    pub(crate) fn clear(&mut self) {
+        self.vertex_data.clear();
+        self.index_data.clear();
+    }
+
+    /// Writes the quads to the GPU buffers, returning the buffers to draw. The buffers are grown to
+    /// the next power of two when they are too small. The quads are kept until clear is called.
+    pub(crate) fn upload(
+        &mut self,
+        display: &glium::Display,
+    ) -> (
+        &glium::VertexBuffer<V>,
+        glium::index::IndexBufferSlice<'_, u32>,
+    ) {
+        let vertex_count = self.vertex_data.len();
+        let index_count = self.index_data.len();
+        if self.vertex_buffer.as_ref().map(|b| b.len()).unwrap_or(0) < vertex_count {
+            self.vertex_buffer = Some(
+                glium::VertexBuffer::empty_dynamic(display, vertex_count.next_power_of_two())
+                    .unwrap_or_else(|e| {
+                        panic!("Failed to create {} vertex buffer: {:?}", self.name, e)
+                    }),
+            );
+        }
+        if self.index_buffer.as_ref().map(|b| b.len()).unwrap_or(0) < index_count {
+            self.index_buffer = Some(
+                glium::IndexBuffer::empty_dynamic(
+                    display,
+                    glium::index::PrimitiveType::TrianglesList,
+                    index_count.next_power_of_two(),
+                )
+                .unwrap_or_else(|e| panic!("Failed to create {} index buffer: {:?}", self.name, e)),
+            );
+        }
+
+        let vertex_buffer = self.vertex_buffer.as_mut().unwrap();
+        let index_buffer = self.index_buffer.as_mut().unwrap();
+        vertex_buffer.invalidate();
+        vertex_buffer
+            .slice_mut(0..vertex_count)
+            .unwrap()
+            .write(&self.vertex_data);
+        index_buffer.invalidate();
+        index_buffer
+            .slice_mut(0..index_count)
+            .unwrap()
+            .write(&self.index_data);
+
+        let vertex_buffer = self.vertex_buffer.as_ref().unwrap();
+        let index_buffer = self.index_buffer.as_ref().unwrap();
+        (vertex_buffer, index_buffer.slice(0..index_count).unwrap())
+    }
+}
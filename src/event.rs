@@ -5,9 +5,13 @@ use crate::event_state::{EventState, MouseButtonState};
 use crate::screen_units::Screen2d;
 use glium::glutin::event as gle;
 use glium::glutin::event::{DeviceId, VirtualKeyCode};
-use glium::glutin::window::WindowId;
+use glium::glutin::window::{Theme, WindowId};
 use noisy_float::prelude::*;
 use std::path::PathBuf;
+use std::time::Instant;
+
+#[cfg(feature = "gilrs")]
+pub use gilrs::{Axis as GamepadAxis, Button as GamepadButton, GamepadId};
 
 pub type AxisId = u32;
 pub type ScanCode = u32;
@@ -15,51 +19,93 @@ pub type ButtonId = u32;
 pub type FingerId = u64;
 
 /// An event from OpenGL. This is a simplified version of the events provided by winit.
+///
+/// When the `serde` feature is enabled, events can be serialized (ex. for recording and replaying
+/// input). Window and device identifiers are opaque, so they are serialized as process-local
+/// numbers and are deserialized as a placeholder identifier (they will not match any real window
+/// or device).
 #[derive(Clone, Debug, PartialEq)]
-pub enum Event {
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub enum Event<T = ()> {
     Placeholder,
 
+    /// Custom event sent using an event loop proxy (see Window::create_proxy).
+    User(T),
+
     AppAwaken,
     AppResume,
     AppSuspend,
     Redraw,
 
-    /// Shift/ctrl/alt changed
-    ModifiersChanged,
+    /// Shift/ctrl/alt/logo changed (contains the new state of each modifier)
+    ModifiersChanged {
+        shift: bool,
+        ctrl: bool,
+        alt: bool,
+        logo: bool,
+    },
 
+    /// Sent when the size of the window changes. This is also sent once for each window when it
+    /// is created (after the first event delivered to the run callback), so the initial size can
+    /// be handled the same way as later changes (ex. when setting up a projection).
     WindowResize {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
         size: Screen2d,
     },
     WindowMove {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
         pos: Screen2d,
     },
     WindowClose {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
     },
     WindowDestroyed {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
     },
     WindowRefresh {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
     },
     WindowFocus {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
     },
     WindowBlur {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+    },
+    /// Sent after the WindowResize event when the window is minimized (see EventState::is_visible).
+    /// Occlusion by other windows is not reported by winit 0.24.
+    WindowMinimize {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+    },
+    /// Sent after the WindowResize event when a minimized window is restored.
+    WindowRestore {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
     },
 
     FileDrop {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
         path: PathBuf,
     },
     FileHover {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
         path: PathBuf,
     },
     FileCancel {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
     },
 
@@ -68,6 +114,7 @@ pub enum Event {
     /// sum-total of mouse motion against screen position will typically be different due to mouse
     /// acceleration, O/S settings, and other factors.
     MouseMotion {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         delta: [f32; 2],
     },
@@ -75,145 +122,434 @@ pub enum Event {
     /// However, there are some drawbacks. Some devices (ex. some touchpads) may not trigger this callback
     /// and will only trigger the MouseWheel callback.
     AnywhereMouseWheel {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         delta: Screen2d,
         delta_line: Option<[f32; 2]>,
     },
 
     MouseMove {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         pos: Screen2d,
     },
     MouseDown {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         button: MouseButton,
     },
     MouseUp {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         button: MouseButton,
     },
+    /// Sent after a MouseUp event when the button was released close to where it was pressed (see
+    /// EventState::set_click_distance). Pressing escape while the button is down cancels the click.
+    MouseClick {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        button: MouseButton,
+        pos: Screen2d,
+    },
+    /// Sent after a MouseMove event when the mouse first moves further than the drag distance from
+    /// where the button was pressed (see EventState::set_drag_distance).
+    DragStart {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        button: MouseButton,
+        from: Screen2d,
+    },
+    /// Sent after each MouseMove event while dragging.
+    DragMove {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        button: MouseButton,
+        from: Screen2d,
+        to: Screen2d,
+        delta: Screen2d,
+    },
+    /// Sent when the button is released after dragging, or when escape is pressed while dragging
+    /// (in which case cancelled is true).
+    DragEnd {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        button: MouseButton,
+        from: Screen2d,
+        to: Screen2d,
+        cancelled: bool,
+    },
+    /// Sent after a MouseDown event when the button was pressed twice in quick succession (see
+    /// EventState::set_double_click_time and EventState::set_double_click_distance).
+    MouseDoubleClick {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
+        device_id: DeviceId,
+        button: MouseButton,
+        pos: Screen2d,
+    },
     MouseWheel {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
+        /// Scroll distance in logical units, including horizontal scrolling (ex. from a tilt wheel
+        /// or a trackpad). Line deltas (from a mouse wheel) are multiplied by the logical line
+        /// height (see EventState::logical_line_height).
         delta: Screen2d,
+        /// Scroll distance in lines, if the device reported lines rather than pixels.
         delta_line: Option<[f32; 2]>,
+        /// Vertical scroll amount in approximate mouse wheel notches (positive is away from the
+        /// user). Line deltas are used as-is, while pixel deltas (ex. from a trackpad) are divided
+        /// by the logical line height (see EventState::set_logical_line_height). This allows a
+        /// single scroll handler to behave similarly across devices.
+        scroll: f32,
         phase: TouchPhase,
     },
     MouseWindowEnter {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
     },
     MouseWindowLeave {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
     },
 
     TouchpadPressure {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         pressure: f32,
         stage: i64,
     },
     Touch {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         finger: FingerId,
         pos: Screen2d,
         phase: TouchPhase,
     },
+    /// Two-finger pinch gesture (requires EventState::set_gestures_enabled). The scale delta is the
+    /// ratio of the new distance between the fingers to the previous distance.
+    Pinch {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        scale_delta: f32,
+        center: Screen2d,
+    },
+    /// Two-finger rotate gesture (requires EventState::set_gestures_enabled). The angle delta is in
+    /// radians, and is positive for clockwise rotation on the screen.
+    Rotate {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        angle_delta: f32,
+        center: Screen2d,
+    },
+    /// One or two-finger pan gesture (requires EventState::set_gestures_enabled).
+    Pan {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        delta: Screen2d,
+        center: Screen2d,
+    },
 
     AxisMotion {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         axis: AxisId,
         delta: f32,
     },
 
     KeyDown {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         code: ScanCode,
         vkey: Option<VirtualKeyCode>,
+        /// Modifier keys that were held down when the key event occurred
+        modifiers: Modifiers,
+        /// Indicates if this is an auto-repeat of a key that is being held down
+        repeat: bool,
     },
     KeyUp {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         code: ScanCode,
         vkey: Option<VirtualKeyCode>,
+        /// Modifier keys that were held down when the key event occurred
+        modifiers: Modifiers,
     },
+    /// Text entered by the user. The ch field is None for control characters and keyboard
+    /// shortcuts (ex. Ctrl+S). Text composed using an IME is delivered as a series of KeyText
+    /// events once committed (the in-progress composition is not reported by winit 0.24).
     KeyText {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
         codepoint: char,
         ch: Option<char>,
     },
+    /// Text that is being composed using an IME, which should be shown at the text cursor until it
+    /// is committed. The cursor is the byte range of the IME cursor/selection within the text.
+    ///
+    /// Reserved: winit 0.24 does not report the composition, so this is never sent yet.
+    ImePreedit {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        text: String,
+        cursor: Option<(usize, usize)>,
+    },
+    /// Text that was composed using an IME and is now final (this replaces the preedit text).
+    ///
+    /// Reserved: winit 0.24 delivers committed text as KeyText events, so this is never sent yet.
+    ImeCommit {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        text: String,
+    },
 
     DeviceAdded {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
     },
     DeviceRemoved {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
     },
     DeviceMotion {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         axis: AxisId,
         delta: f32,
     },
     DeviceButtonDown {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         button: ButtonId,
     },
     DeviceButtonUp {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         button: ButtonId,
     },
     DeviceKeyDown {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         code: ScanCode,
         vkey: Option<VirtualKeyCode>,
     },
     DeviceKeyUp {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         code: ScanCode,
         vkey: Option<VirtualKeyCode>,
     },
     DeviceText {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::device"))]
         device_id: DeviceId,
         codepoint: char,
         ch: Option<char>,
     },
 
+    /// A gamepad was connected (requires the `gilrs` feature).
+    #[cfg(feature = "gilrs")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    GamepadConnected {
+        gamepad_id: GamepadId,
+    },
+    /// A gamepad was disconnected (requires the `gilrs` feature).
+    #[cfg(feature = "gilrs")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    GamepadDisconnected {
+        gamepad_id: GamepadId,
+    },
+    #[cfg(feature = "gilrs")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    GamepadButtonDown {
+        gamepad_id: GamepadId,
+        button: GamepadButton,
+    },
+    #[cfg(feature = "gilrs")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    GamepadButtonUp {
+        gamepad_id: GamepadId,
+        button: GamepadButton,
+    },
+    /// Value of an analog button (such as a trigger) changed. The value is between 0.0 and 1.0.
+    #[cfg(feature = "gilrs")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    GamepadButtonChanged {
+        gamepad_id: GamepadId,
+        button: GamepadButton,
+        value: f32,
+    },
+    /// Value of an axis (such as a thumb stick) changed. The value is between -1.0 and 1.0.
+    #[cfg(feature = "gilrs")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    GamepadAxis {
+        gamepad_id: GamepadId,
+        axis: GamepadAxis,
+        value: f32,
+    },
+
+    /// The system theme changed between light and dark mode (see EventState::is_dark_theme).
+    ThemeChanged {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
+        win_id: WindowId,
+        dark: bool,
+    },
+
     HiDpiFactorChanged {
+        #[cfg_attr(feature = "serde", serde(with = "id_serde::window"))]
         win_id: WindowId,
         factor: f32,
     },
 }
-impl Event {
+impl<T> Event<T> {
     pub fn is_mouse_event(&self) -> bool {
         match *self {
             Event::MouseMove { .. }
             | Event::MouseDown { .. }
             | Event::MouseUp { .. }
+            | Event::MouseClick { .. }
+            | Event::MouseDoubleClick { .. }
+            | Event::DragStart { .. }
+            | Event::DragMove { .. }
+            | Event::DragEnd { .. }
             | Event::MouseWheel { .. }
             | Event::MouseWindowEnter { .. }
             | Event::MouseWindowLeave { .. } => true,
             _ => false,
         }
     }
+    /// Window that the event is for, or None if the event is not associated with a window (ex.
+    /// device events).
+    pub fn window_id(&self) -> Option<WindowId> {
+        match *self {
+            Event::WindowResize { win_id, .. }
+            | Event::WindowMove { win_id, .. }
+            | Event::WindowClose { win_id, .. }
+            | Event::WindowDestroyed { win_id, .. }
+            | Event::WindowRefresh { win_id, .. }
+            | Event::WindowFocus { win_id, .. }
+            | Event::WindowBlur { win_id, .. }
+            | Event::WindowMinimize { win_id, .. }
+            | Event::WindowRestore { win_id, .. }
+            | Event::FileDrop { win_id, .. }
+            | Event::FileHover { win_id, .. }
+            | Event::FileCancel { win_id, .. }
+            | Event::MouseMove { win_id, .. }
+            | Event::MouseDown { win_id, .. }
+            | Event::MouseUp { win_id, .. }
+            | Event::MouseClick { win_id, .. }
+            | Event::DragStart { win_id, .. }
+            | Event::DragMove { win_id, .. }
+            | Event::DragEnd { win_id, .. }
+            | Event::MouseDoubleClick { win_id, .. }
+            | Event::MouseWheel { win_id, .. }
+            | Event::MouseWindowEnter { win_id, .. }
+            | Event::MouseWindowLeave { win_id, .. }
+            | Event::TouchpadPressure { win_id, .. }
+            | Event::Touch { win_id, .. }
+            | Event::Pinch { win_id, .. }
+            | Event::Rotate { win_id, .. }
+            | Event::Pan { win_id, .. }
+            | Event::AxisMotion { win_id, .. }
+            | Event::KeyDown { win_id, .. }
+            | Event::KeyUp { win_id, .. }
+            | Event::KeyText { win_id, .. }
+            | Event::ImePreedit { win_id, .. }
+            | Event::ImeCommit { win_id, .. }
+            | Event::ThemeChanged { win_id, .. }
+            | Event::HiDpiFactorChanged { win_id, .. } => Some(win_id),
+            _ => None,
+        }
+    }
+    pub fn is_keyboard_event(&self) -> bool {
+        match *self {
+            Event::KeyDown { .. }
+            | Event::KeyUp { .. }
+            | Event::KeyText { .. }
+            | Event::ImePreedit { .. }
+            | Event::ImeCommit { .. }
+            | Event::ModifiersChanged { .. }
+            | Event::DeviceKeyDown { .. }
+            | Event::DeviceKeyUp { .. }
+            | Event::DeviceText { .. } => true,
+            _ => false,
+        }
+    }
+    pub fn is_window_event(&self) -> bool {
+        match *self {
+            Event::WindowResize { .. }
+            | Event::WindowMove { .. }
+            | Event::WindowClose { .. }
+            | Event::WindowDestroyed { .. }
+            | Event::WindowRefresh { .. }
+            | Event::WindowFocus { .. }
+            | Event::WindowBlur { .. }
+            | Event::WindowMinimize { .. }
+            | Event::WindowRestore { .. }
+            | Event::ThemeChanged { .. }
+            | Event::HiDpiFactorChanged { .. } => true,
+            _ => false,
+        }
+    }
+    pub fn is_touch_event(&self) -> bool {
+        match *self {
+            Event::TouchpadPressure { .. }
+            | Event::Touch { .. }
+            | Event::Pinch { .. }
+            | Event::Rotate { .. }
+            | Event::Pan { .. } => true,
+            _ => false,
+        }
+    }
 
-    pub fn from_gl<T>(src: &gle::Event<T>, state: &mut EventState) -> Event {
+    pub fn from_gl(src: &gle::Event<T>, state: &mut EventState) -> Event<T>
+    where
+        T: Clone,
+    {
+        state.discard_stale_input();
         match *src {
             gle::Event::WindowEvent {
                 window_id,
                 ref event,
-            } => Self::from_window_event(window_id, event, state),
+            } => {
+                let e = Self::from_window_event(window_id, event, state);
+                if !matches!(e, Event::Placeholder) {
+                    state.last_activity = Instant::now();
+                }
+                e
+            }
 
             gle::Event::DeviceEvent {
                 device_id,
                 ref event,
-            } => Self::from_device_event(device_id, event, state),
+            } => {
+                let e = Self::from_device_event(device_id, event, state);
+                if !matches!(e, Event::Placeholder) && state.idle_includes_device_events {
+                    state.last_activity = Instant::now();
+                }
+                e
+            }
 
             gle::Event::Suspended => Event::AppSuspend,
             gle::Event::Resumed => Event::AppResume,
@@ -221,32 +557,73 @@ impl Event {
             gle::Event::RedrawRequested(_) => Event::Redraw,
             gle::Event::RedrawEventsCleared => Event::Redraw,
 
+            gle::Event::UserEvent(ref user_evt) => Event::User(user_evt.clone()),
+
             // New events (ignored for now)
             gle::Event::NewEvents(_cause) => Event::Placeholder,
-            gle::Event::UserEvent(..) => Event::Placeholder,
             gle::Event::MainEventsCleared => Event::Placeholder,
             gle::Event::LoopDestroyed => Event::Placeholder,
         }
     }
 
+    /// Converts a gilrs gamepad event. Returns None for events that are not reported (such as
+    /// repeated button presses).
+    #[cfg(feature = "gilrs")]
+    pub fn from_gilrs(src: &gilrs::Event) -> Option<Event<T>> {
+        let gamepad_id = src.id;
+        match src.event {
+            gilrs::EventType::Connected => Some(Event::GamepadConnected { gamepad_id }),
+            gilrs::EventType::Disconnected => Some(Event::GamepadDisconnected { gamepad_id }),
+            gilrs::EventType::ButtonPressed(button, _) => {
+                Some(Event::GamepadButtonDown { gamepad_id, button })
+            }
+            gilrs::EventType::ButtonReleased(button, _) => {
+                Some(Event::GamepadButtonUp { gamepad_id, button })
+            }
+            gilrs::EventType::ButtonChanged(button, value, _) => {
+                Some(Event::GamepadButtonChanged {
+                    gamepad_id,
+                    button,
+                    value,
+                })
+            }
+            gilrs::EventType::AxisChanged(axis, value, _) => Some(Event::GamepadAxis {
+                gamepad_id,
+                axis,
+                value,
+            }),
+            gilrs::EventType::ButtonRepeated(..) | gilrs::EventType::Dropped => None,
+        }
+    }
+
     fn from_window_event<'a>(
         win_id: WindowId,
         evt: &gle::WindowEvent,
         evt_state: &mut EventState,
-    ) -> Event {
+    ) -> Event<T> {
         match evt {
             gle::WindowEvent::Resized(phys_size) => {
-                let size = {
+                // Minimized windows are reported as being resized to zero (winit 0.24 does not
+                // have a separate minimize event)
+                let minimized = phys_size.width == 0 && phys_size.height == 0;
+                let f = evt_state.win_hidpi_r32(win_id);
+                let (size, was_minimized) = {
                     let w = evt_state.get_or_create_win(win_id);
-                    let f = w.hidpi_factor;
                     let size = Screen2d::from_physical_size_u32(phys_size, f.const_raw());
                     w.dim = size;
-                    size
+                    let was_minimized = w.minimized;
+                    w.minimized = minimized;
+                    (size, was_minimized)
                 };
+                if minimized && !was_minimized {
+                    evt_state.push_window_event(PendingEvent::WindowMinimize { win_id });
+                } else if !minimized && was_minimized {
+                    evt_state.push_window_event(PendingEvent::WindowRestore { win_id });
+                }
                 Event::WindowResize { win_id, size }
             }
             gle::WindowEvent::Moved(logical_pos) => {
-                let f = evt_state.get_or_create_win(win_id).hidpi_factor;
+                let f = evt_state.win_hidpi_r32(win_id);
                 let pos = Screen2d::from_physical_position_i32(logical_pos, f);
                 Event::WindowMove { win_id, pos }
             }
@@ -257,7 +634,13 @@ impl Event {
             }
             //gle::WindowEvent::Refresh => Event::WindowRefresh { win_id },
             gle::WindowEvent::Focused(true) => Event::WindowFocus { win_id },
-            gle::WindowEvent::Focused(false) => Event::WindowBlur { win_id },
+            gle::WindowEvent::Focused(false) => {
+                // Key releases are not reported while the window does not have focus
+                let released = std::mem::take(&mut evt_state.keys_down);
+                evt_state.keys_just_released.extend(released);
+                evt_state.scancodes_down.clear();
+                Event::WindowBlur { win_id }
+            }
 
             gle::WindowEvent::DroppedFile(ref path) => Event::FileDrop {
                 win_id,
@@ -270,18 +653,23 @@ impl Event {
             gle::WindowEvent::HoveredFileCancelled => Event::FileCancel { win_id },
 
             gle::WindowEvent::ReceivedCharacter(codepoint) => {
-                if evt_state.ctrl_down {
-                    Event::KeyText {
-                        win_id,
-                        codepoint: *codepoint,
-                        ch: None,
-                    }
+                let after_cr = evt_state.last_codepoint == Some('\r');
+                evt_state.last_codepoint = Some(*codepoint);
+
+                // Ctrl is used for shortcuts, but AltGr is reported as Ctrl+Alt and is used to
+                // type characters on many layouts (ex. @ and { on German keyboards)
+                let ch = if evt_state.ctrl_down && !evt_state.alt_down {
+                    None
+                } else if *codepoint == '\n' && after_cr && evt_state.translate_cr {
+                    // The new line was already produced by the \r of a \r\n sequence
+                    None
                 } else {
-                    Event::KeyText {
-                        win_id,
-                        codepoint: *codepoint,
-                        ch: Self::text_char(*codepoint),
-                    }
+                    Self::text_char(*codepoint, evt_state.translate_cr)
+                };
+                Event::KeyText {
+                    win_id,
+                    codepoint: *codepoint,
+                    ch,
                 }
             }
             gle::WindowEvent::KeyboardInput {
@@ -289,22 +677,37 @@ impl Event {
                 input,
                 is_synthetic,
             } => {
+                // Synthetic events are sent for keys pressed/released while the window did not
+                // have focus, so they are tracked the same as regular key presses.
                 let _ = is_synthetic;
                 //Self::set_modifiers(evt_state, &input.modifiers);
 
-                match (input.state, input.virtual_keycode) {
-                    (gle::ElementState::Pressed, Some(VirtualKeyCode::Escape)) => {
-                        if evt_state.mouse_left.pressed {
-                            evt_state.mouse_left.cancelled = true;
-                        }
-                        if evt_state.mouse_right.pressed {
-                            evt_state.mouse_right.cancelled = true;
+                // The key is already recorded as pressed when the OS sends an auto-repeat
+                let repeat = match input.state {
+                    gle::ElementState::Pressed => {
+                        let repeat = !evt_state.scancodes_down.insert(input.scancode);
+                        if let Some(vkey) = input.virtual_keycode {
+                            if evt_state.keys_down.insert(vkey) {
+                                evt_state.keys_just_pressed.insert(vkey);
+                            }
                         }
-                        if evt_state.mouse_middle.pressed {
-                            evt_state.mouse_middle.cancelled = true;
+                        repeat
+                    }
+                    gle::ElementState::Released => {
+                        evt_state.scancodes_down.remove(&input.scancode);
+                        if let Some(vkey) = input.virtual_keycode {
+                            if evt_state.keys_down.remove(&vkey) {
+                                evt_state.keys_just_released.insert(vkey);
+                            }
                         }
+                        false
                     }
-                    _ => {}
+                };
+
+                if let (gle::ElementState::Pressed, Some(VirtualKeyCode::Escape)) =
+                    (input.state, input.virtual_keycode)
+                {
+                    Self::cancel_mouse_buttons(evt_state, win_id);
                 }
 
                 match input.state {
@@ -313,12 +716,15 @@ impl Event {
                         device_id: *device_id,
                         code: input.scancode,
                         vkey: input.virtual_keycode,
+                        modifiers: evt_state.modifiers(),
+                        repeat,
                     },
                     gle::ElementState::Released => Event::KeyUp {
                         win_id,
                         device_id: *device_id,
                         code: input.scancode,
                         vkey: input.virtual_keycode,
+                        modifiers: evt_state.modifiers(),
                     },
                 }
             }
@@ -328,12 +734,14 @@ impl Event {
                 position,
                 ..
             } => {
-                let f = evt_state.get_or_create_win(win_id).hidpi_factor;
+                let f = evt_state.win_hidpi_r32(win_id);
                 let pos = Screen2d::from_physical_position_f64(position, f);
+                let prev_pos = evt_state.mouse_pos;
                 evt_state.mouse_pos = pos;
                 if !evt_state.is_any_mouse_button_pressed() {
                     evt_state.mouse_activity_start = pos;
                 }
+                Self::update_drags(evt_state, win_id, prev_pos, pos);
                 Event::MouseMove {
                     win_id,
                     device_id: *device_id,
@@ -361,29 +769,42 @@ impl Event {
                 ..
             } => match delta {
                 gle::MouseScrollDelta::LineDelta(dx, dy) => {
-                    let f = evt_state.get_or_create_win(win_id).hidpi_factor;
+                    let f = evt_state.win_hidpi_r32(win_id);
                     let delta = Screen2d::from_line_delta(
                         r32(*dx),
                         r32(*dy),
                         evt_state.logical_line_height,
                         f,
                     );
+                    evt_state.wheel_total += delta;
+                    evt_state.wheel_line_total[0] += r32(*dx);
+                    evt_state.wheel_line_total[1] += r32(*dy);
+                    evt_state.last_scroll = [r32(*dx), r32(*dy)];
                     Event::MouseWheel {
                         win_id,
                         device_id: *device_id,
                         delta,
                         delta_line: Some([*dx, *dy]),
+                        scroll: *dy,
                         phase: TouchPhase::from_gl(*phase),
                     }
                 }
                 gle::MouseScrollDelta::PixelDelta(phys_pos) => {
-                    let f = evt_state.get_or_create_win(win_id).hidpi_factor;
+                    let f = evt_state.win_hidpi_r32(win_id);
                     let delta = Screen2d::from_physical_position_f64(phys_pos, f);
+                    evt_state.wheel_total += delta;
+                    let line_height = evt_state.logical_line_height;
+                    evt_state.last_scroll = [
+                        r32(delta.logical()[0]) / line_height,
+                        r32(delta.logical()[1]) / line_height,
+                    ];
+                    let scroll = evt_state.last_scroll[1].raw();
                     Event::MouseWheel {
                         win_id,
                         device_id: *device_id,
                         delta,
                         delta_line: None,
+                        scroll,
                         phase: TouchPhase::from_gl(*phase),
                     }
                 }
@@ -396,10 +817,31 @@ impl Event {
             } => match state {
                 gle::ElementState::Pressed => {
                     let pos = evt_state.mouse_pos;
+                    let now = Instant::now();
+                    let double_click_time = evt_state.double_click_time;
+                    let double_click_dist = evt_state.double_click_distance.raw();
+                    let mut double_click = false;
                     if let Some(d) = Self::mouse_data_for(evt_state, *button) {
+                        if let Some(t) = d.pressed_time {
+                            double_click = now.duration_since(t) <= double_click_time
+                                && pos.distance(&d.pressed_at) <= double_click_dist;
+                        }
+                        d.just_pressed |= !d.pressed;
                         d.pressed = true;
                         d.pressed_at = pos;
+                        // Do not allow a third click to count as another double-click
+                        d.pressed_time = if double_click { None } else { Some(now) };
                         d.cancelled = false;
+                        d.dragging = false;
+                    }
+                    evt_state.double_click = double_click;
+                    if double_click {
+                        evt_state.pending.push_back(PendingEvent::MouseDoubleClick {
+                            win_id,
+                            device_id: *device_id,
+                            button: MouseButton::from_gl(*button),
+                            pos,
+                        });
                     }
                     Event::MouseDown {
                         win_id,
@@ -408,8 +850,35 @@ impl Event {
                     }
                 }
                 gle::ElementState::Released => {
+                    let pos = evt_state.mouse_pos;
+                    let click_dist = evt_state.click_distance.raw();
+                    let mut click = false;
+                    let mut drag_end = None;
                     if let Some(d) = Self::mouse_data_for(evt_state, *button) {
+                        click = d.pressed
+                            && !d.cancelled
+                            && !d.dragging
+                            && pos.distance(&d.pressed_at) <= click_dist;
+                        d.just_released |= d.pressed;
                         d.pressed = false;
+                        if d.dragging {
+                            d.dragging = false;
+                            drag_end = Some(PendingEvent::DragEnd {
+                                win_id,
+                                button: d.button.clone(),
+                                from: d.pressed_at,
+                                to: pos,
+                                cancelled: false,
+                            });
+                        }
+                    }
+                    evt_state.pending.extend(drag_end);
+                    if click {
+                        evt_state.pending.push_back(PendingEvent::MouseClick {
+                            win_id,
+                            button: MouseButton::from_gl(*button),
+                            pos,
+                        });
                     }
                     Event::MouseUp {
                         win_id,
@@ -441,8 +910,9 @@ impl Event {
                 stage: *stage,
             },
             gle::WindowEvent::Touch(ref t) => {
-                let f = evt_state.get_or_create_win(win_id).hidpi_factor;
+                let f = evt_state.win_hidpi_r32(win_id);
                 let s = Screen2d::from_physical_position_f64(&t.location, f);
+                Self::update_touch(evt_state, win_id, t.id, s, t.phase);
                 Event::Touch {
                     win_id,
                     device_id: t.device_id,
@@ -460,12 +930,12 @@ impl Event {
                 evt_state.get_or_create_win(win_id).hidpi_factor = r32(factor);
                 Event::HiDpiFactorChanged { win_id, factor }
             }
-            gle::WindowEvent::ModifiersChanged(m) => {
-                Self::set_modifiers(evt_state, m);
-                // TODO! Assign modifiers in the future
-                Event::ModifiersChanged
+            gle::WindowEvent::ModifiersChanged(m) => Self::set_modifiers(evt_state, m),
+            gle::WindowEvent::ThemeChanged(theme) => {
+                let dark = *theme == Theme::Dark;
+                evt_state.dark_theme = dark;
+                Event::ThemeChanged { win_id, dark }
             }
-            gle::WindowEvent::ThemeChanged(_t) => Event::Placeholder,
         }
     }
 
@@ -473,14 +943,18 @@ impl Event {
         device_id: DeviceId,
         evt: &gle::DeviceEvent,
         state: &mut EventState,
-    ) -> Event {
+    ) -> Event<T> {
         match *evt {
             gle::DeviceEvent::Added => Event::DeviceAdded { device_id },
             gle::DeviceEvent::Removed => Event::DeviceRemoved { device_id },
-            gle::DeviceEvent::MouseMotion { delta } => Event::MouseMotion {
-                device_id,
-                delta: [delta.0 as f32, delta.1 as f32],
-            },
+            gle::DeviceEvent::MouseMotion { delta } => {
+                state.raw_motion[0] += r32(delta.0 as f32);
+                state.raw_motion[1] += r32(delta.1 as f32);
+                Event::MouseMotion {
+                    device_id,
+                    delta: [delta.0 as f32, delta.1 as f32],
+                }
+            }
             gle::DeviceEvent::MouseWheel { delta } => match delta {
                 gle::MouseScrollDelta::LineDelta(dx, dy) => {
                     let f = state.hidpi_factor_r32();
@@ -533,11 +1007,140 @@ impl Event {
             gle::DeviceEvent::Text { codepoint } => Event::DeviceText {
                 device_id,
                 codepoint,
-                ch: Self::text_char(codepoint),
+                ch: Self::text_char(codepoint, state.translate_cr),
             },
         }
     }
 
+    fn cancel_mouse_buttons(state: &mut EventState, win_id: WindowId) {
+        let pos = state.mouse_pos;
+        let mut events = Vec::new();
+        for d in &mut [
+            &mut state.mouse_left,
+            &mut state.mouse_right,
+            &mut state.mouse_middle,
+        ] {
+            if d.pressed {
+                d.cancelled = true;
+            }
+            if d.dragging {
+                d.dragging = false;
+                events.push(PendingEvent::DragEnd {
+                    win_id,
+                    button: d.button.clone(),
+                    from: d.pressed_at,
+                    to: pos,
+                    cancelled: true,
+                });
+            }
+        }
+        state.pending.extend(events);
+    }
+
+    fn update_drags(state: &mut EventState, win_id: WindowId, prev_pos: Screen2d, pos: Screen2d) {
+        let drag_dist = state.drag_distance.raw();
+        let mut events = Vec::new();
+        for d in &mut [
+            &mut state.mouse_left,
+            &mut state.mouse_right,
+            &mut state.mouse_middle,
+        ] {
+            if !d.pressed || d.cancelled {
+                continue;
+            }
+            if d.dragging {
+                events.push(PendingEvent::DragMove {
+                    win_id,
+                    button: d.button.clone(),
+                    from: d.pressed_at,
+                    to: pos,
+                    delta: pos - prev_pos,
+                });
+            } else if pos.distance(&d.pressed_at) > drag_dist {
+                d.dragging = true;
+                events.push(PendingEvent::DragStart {
+                    win_id,
+                    button: d.button.clone(),
+                    from: d.pressed_at,
+                });
+            }
+        }
+        state.pending.extend(events);
+    }
+
+    fn update_touch(
+        state: &mut EventState,
+        win_id: WindowId,
+        finger: FingerId,
+        pos: Screen2d,
+        phase: gle::TouchPhase,
+    ) {
+        let prev: Vec<Screen2d> = state.touches.values().take(2).copied().collect();
+        match phase {
+            gle::TouchPhase::Started => {
+                state.touches.insert(finger, pos);
+                return;
+            }
+            gle::TouchPhase::Moved => {
+                if let Some(p) = state.touches.get_mut(&finger) {
+                    *p = pos;
+                }
+            }
+            gle::TouchPhase::Ended | gle::TouchPhase::Cancelled => {
+                state.touches.remove(&finger);
+                return;
+            }
+        }
+        if !state.gestures {
+            return;
+        }
+
+        // Gestures use the first two fingers (the remaining finger is used to pan when one of the
+        // two fingers is lifted)
+        let next: Vec<Screen2d> = state.touches.values().take(2).copied().collect();
+        match (prev.as_slice(), next.as_slice()) {
+            (&[a0, b0], &[a1, b1]) => {
+                let center0 = a0.lerp(b0, 0.5);
+                let center1 = a1.lerp(b1, 0.5);
+                let span0 = b0 - a0;
+                let span1 = b1 - a1;
+                if !span0.is_zero() && !span1.is_zero() {
+                    let scale_delta = span1.length() / span0.length();
+                    if scale_delta != 1.0 {
+                        state.pending.push_back(PendingEvent::Pinch {
+                            win_id,
+                            scale_delta: r32(scale_delta),
+                            center: center1,
+                        });
+                    }
+                    let angle_delta = span0.cross(&span1).atan2(span0.dot(&span1));
+                    if angle_delta != 0.0 {
+                        state.pending.push_back(PendingEvent::Rotate {
+                            win_id,
+                            angle_delta: r32(angle_delta),
+                            center: center1,
+                        });
+                    }
+                }
+                if center1 != center0 {
+                    state.pending.push_back(PendingEvent::Pan {
+                        win_id,
+                        delta: center1 - center0,
+                        center: center1,
+                    });
+                }
+            }
+            (&[p0], &[p1]) if p1 != p0 => {
+                state.pending.push_back(PendingEvent::Pan {
+                    win_id,
+                    delta: p1 - p0,
+                    center: p1,
+                });
+            }
+            _ => {}
+        }
+    }
+
     fn mouse_data_for<'a>(
         state: &'a mut EventState,
         b: gle::MouseButton,
@@ -550,19 +1153,29 @@ impl Event {
         }
     }
 
-    fn set_modifiers(state: &mut EventState, modifiers: &gle::ModifiersState) {
+    fn set_modifiers(state: &mut EventState, modifiers: &gle::ModifiersState) -> Event<T> {
         state.shift_down = modifiers.shift();
         state.ctrl_down = modifiers.ctrl();
         state.alt_down = modifiers.alt();
         state.logo_down = modifiers.logo();
+        Event::ModifiersChanged {
+            shift: state.shift_down,
+            ctrl: state.ctrl_down,
+            alt: state.alt_down,
+            logo: state.logo_down,
+        }
     }
 
-    fn text_char(ch: char) -> Option<char> {
+    fn text_char(ch: char, translate_cr: bool) -> Option<char> {
         if (ch as u32) <= 31 {
-            if ch == '\t' {
+            if ch == '\t' || ch == '\n' {
                 Some(ch)
             } else if ch == '\r' {
-                Some('\n')
+                if translate_cr {
+                    Some('\n')
+                } else {
+                    Some(ch)
+                }
             } else {
                 None
             }
@@ -574,13 +1187,207 @@ impl Event {
     }
 }
 
+/// Event that is generated by the event state in addition to the event currently being processed.
+/// These are delivered after the current event (see EventState::next_pending_event). Input events
+/// that were not taken are discarded when the next event is processed, and only the latest window
+/// event of each kind is kept for a window, so the queue stays small when it is never drained.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum PendingEvent {
+    WindowResize {
+        win_id: WindowId,
+        size: Screen2d,
+    },
+    WindowMinimize {
+        win_id: WindowId,
+    },
+    WindowRestore {
+        win_id: WindowId,
+    },
+    MouseClick {
+        win_id: WindowId,
+        button: MouseButton,
+        pos: Screen2d,
+    },
+    MouseDoubleClick {
+        win_id: WindowId,
+        device_id: DeviceId,
+        button: MouseButton,
+        pos: Screen2d,
+    },
+    DragStart {
+        win_id: WindowId,
+        button: MouseButton,
+        from: Screen2d,
+    },
+    DragMove {
+        win_id: WindowId,
+        button: MouseButton,
+        from: Screen2d,
+        to: Screen2d,
+        delta: Screen2d,
+    },
+    DragEnd {
+        win_id: WindowId,
+        button: MouseButton,
+        from: Screen2d,
+        to: Screen2d,
+        cancelled: bool,
+    },
+    Pinch {
+        win_id: WindowId,
+        scale_delta: R32,
+        center: Screen2d,
+    },
+    Rotate {
+        win_id: WindowId,
+        angle_delta: R32,
+        center: Screen2d,
+    },
+    Pan {
+        win_id: WindowId,
+        delta: Screen2d,
+        center: Screen2d,
+    },
+}
+impl PendingEvent {
+    /// Indicates if the event was generated from user input (as opposed to window changes).
+    pub(crate) fn is_input(&self) -> bool {
+        !matches!(
+            self,
+            PendingEvent::WindowResize { .. }
+                | PendingEvent::WindowMinimize { .. }
+                | PendingEvent::WindowRestore { .. }
+        )
+    }
+    /// Indicates if this (undelivered) event is out of date once the other event is queued.
+    pub(crate) fn is_replaced_by(&self, other: &PendingEvent) -> bool {
+        use PendingEvent::*;
+        match (self, other) {
+            (WindowResize { win_id: a, .. }, WindowResize { win_id: b, .. }) => a == b,
+            (WindowMinimize { win_id: a }, WindowMinimize { win_id: b })
+            | (WindowMinimize { win_id: a }, WindowRestore { win_id: b })
+            | (WindowRestore { win_id: a }, WindowMinimize { win_id: b })
+            | (WindowRestore { win_id: a }, WindowRestore { win_id: b }) => a == b,
+            _ => false,
+        }
+    }
+    pub(crate) fn into_event<T>(self) -> Event<T> {
+        match self {
+            PendingEvent::WindowResize { win_id, size } => Event::WindowResize { win_id, size },
+            PendingEvent::WindowMinimize { win_id } => Event::WindowMinimize { win_id },
+            PendingEvent::WindowRestore { win_id } => Event::WindowRestore { win_id },
+            PendingEvent::MouseClick {
+                win_id,
+                button,
+                pos,
+            } => Event::MouseClick {
+                win_id,
+                button,
+                pos,
+            },
+            PendingEvent::MouseDoubleClick {
+                win_id,
+                device_id,
+                button,
+                pos,
+            } => Event::MouseDoubleClick {
+                win_id,
+                device_id,
+                button,
+                pos,
+            },
+            PendingEvent::DragStart {
+                win_id,
+                button,
+                from,
+            } => Event::DragStart {
+                win_id,
+                button,
+                from,
+            },
+            PendingEvent::DragMove {
+                win_id,
+                button,
+                from,
+                to,
+                delta,
+            } => Event::DragMove {
+                win_id,
+                button,
+                from,
+                to,
+                delta,
+            },
+            PendingEvent::DragEnd {
+                win_id,
+                button,
+                from,
+                to,
+                cancelled,
+            } => Event::DragEnd {
+                win_id,
+                button,
+                from,
+                to,
+                cancelled,
+            },
+            PendingEvent::Pinch {
+                win_id,
+                scale_delta,
+                center,
+            } => Event::Pinch {
+                win_id,
+                scale_delta: scale_delta.raw(),
+                center,
+            },
+            PendingEvent::Rotate {
+                win_id,
+                angle_delta,
+                center,
+            } => Event::Rotate {
+                win_id,
+                angle_delta: angle_delta.raw(),
+                center,
+            },
+            PendingEvent::Pan {
+                win_id,
+                delta,
+                center,
+            } => Event::Pan {
+                win_id,
+                delta,
+                center,
+            },
+        }
+    }
+}
+
+/// Snapshot of the modifier keys that were held down at the time of an event.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Modifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    pub logo: bool,
+}
+
 /// Describes a button of a mouse controller.
 #[derive(Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum MouseButton {
     Left,
     Right,
     Middle,
-    Other(u8),
+    Other(u16),
 }
 impl MouseButton {
     fn from_gl(btn: gle::MouseButton) -> Self {
@@ -588,12 +1395,17 @@ impl MouseButton {
             gle::MouseButton::Left => MouseButton::Left,
             gle::MouseButton::Right => MouseButton::Right,
             gle::MouseButton::Middle => MouseButton::Middle,
-            gle::MouseButton::Other(n) => MouseButton::Other(n as u8),
+            gle::MouseButton::Other(n) => MouseButton::Other(n),
         }
     }
 }
 /// Describes touch-screen input state.
 #[derive(Debug, Hash, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum TouchPhase {
     Started,
     Moved,
@@ -610,3 +1422,77 @@ impl TouchPhase {
         }
     }
 }
+
+/// Number that identifies the window (ex. for logging, or for keying per-window state). The same
+/// window always has the same number, but the numbers are process-local: they are not stable
+/// between runs of the application, and should not be saved.
+pub fn window_id_u64(id: WindowId) -> u64 {
+    id_hash(&id)
+}
+/// Number that identifies the input device (see window_id_u64).
+pub fn device_id_u64(id: DeviceId) -> u64 {
+    id_hash(&id)
+}
+fn id_hash<T: std::hash::Hash>(id: &T) -> u64 {
+    use std::hash::Hasher;
+    // The default hasher always starts with the same keys, so equal ids produce equal numbers
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    id.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(feature = "serde")]
+mod id_serde {
+    use serde_crate::{Deserialize, Deserializer, Serializer};
+
+    pub mod window {
+        use super::*;
+        use glium::glutin::window::WindowId;
+
+        pub fn serialize<S: Serializer>(id: &WindowId, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_u64(crate::event::window_id_u64(*id))
+        }
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<WindowId, D::Error> {
+            let _ = u64::deserialize(d)?;
+            // Safety: the dummy ID is only used as a placeholder and is never given to winit
+            Ok(unsafe { WindowId::dummy() })
+        }
+    }
+
+    pub mod device {
+        use super::*;
+        use glium::glutin::event::DeviceId;
+
+        pub fn serialize<S: Serializer>(id: &DeviceId, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_u64(crate::event::device_id_u64(*id))
+        }
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<DeviceId, D::Error> {
+            let _ = u64::deserialize(d)?;
+            // Safety: the dummy ID is only used as a placeholder and is never given to winit
+            Ok(unsafe { DeviceId::dummy() })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mouse_button_other_keeps_full_value() {
+        let button = MouseButton::from_gl(gle::MouseButton::Other(300));
+        assert_eq!(button, MouseButton::Other(300));
+        // 300 truncated to a u8 would be 44
+        assert_ne!(button, MouseButton::from_gl(gle::MouseButton::Other(44)));
+    }
+
+    #[test]
+    fn theme_changed_is_window_event() {
+        let e: Event<()> = Event::ThemeChanged {
+            win_id: unsafe { WindowId::dummy() },
+            dark: true,
+        };
+        assert!(e.is_window_event());
+        assert!(e.window_id().is_some());
+    }
+}
@@ -1,7 +1,10 @@
-use crate::event::{Event, MouseButton};
+use crate::event::{Event, FingerId, Modifiers, MouseButton, PendingEvent, ScanCode};
 use crate::screen_units::Screen2d;
 use glium::glutin as gl;
+use glium::glutin::event::VirtualKeyCode;
 use noisy_float::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::time::{Duration, Instant};
 
 /// Persistant state associated with the events. This keeps track of things like which control keys
 /// are currently pressed, location of the mouse, and the state of the mouse buttons.
@@ -17,20 +20,50 @@ pub struct EventState {
     pub alt_down: bool,
     pub ctrl_down: bool,
     pub logo_down: bool,
+    pub(crate) keys_down: BTreeSet<VirtualKeyCode>,
+    pub(crate) keys_just_pressed: BTreeSet<VirtualKeyCode>,
+    pub(crate) keys_just_released: BTreeSet<VirtualKeyCode>,
+    pub(crate) scancodes_down: BTreeSet<ScanCode>,
     pub windows: Vec<WindowData>,
+    pub(crate) wheel_total: Screen2d,
+    pub(crate) wheel_line_total: [R32; 2],
+    pub(crate) last_scroll: [R32; 2],
+    pub(crate) raw_motion: [R32; 2],
+    pub(crate) touches: BTreeMap<FingerId, Screen2d>,
+    pub(crate) gestures: bool,
+    pub(crate) last_activity: Instant,
+    pub(crate) idle_includes_device_events: bool,
+    pub(crate) dark_theme: bool,
+    pub(crate) last_codepoint: Option<char>,
+    pub(crate) translate_cr: bool,
     pub(crate) logical_line_height: R32,
+    pub(crate) forced_hidpi: Option<R32>,
+    pub(crate) click_distance: R32,
+    pub(crate) drag_distance: R32,
+    pub(crate) double_click: bool,
+    pub(crate) double_click_time: Duration,
+    pub(crate) double_click_distance: R32,
+    pub(crate) pending: VecDeque<PendingEvent>,
+    pub(crate) frame_start: InputSnapshot,
 }
 impl EventState {
     pub fn new(display: &glium::Display) -> Self {
-        use glium::backend::Facade;
-        let hidpi_factor = display.gl_window().window().scale_factor() as f32;
-        let win_id = display.gl_window().window().id();
-        let win_dim = display.get_context().get_framebuffer_dimensions();
-        let win_dim = Screen2d::from_physical_u32([win_dim.0, win_dim.1], hidpi_factor);
+        Self::with_window(
+            WindowData::from_display(display),
+            initial_dark_theme(display),
+        )
+    }
+    fn with_window(win_data: WindowData, dark_theme: bool) -> Self {
+        let hidpi_factor = win_data.hidpi_factor();
+        // The OS does not necessarily send a resize event when the window is created
+        let initial_resize = PendingEvent::WindowResize {
+            win_id: win_data.id,
+            size: win_data.dim,
+        };
 
         Self {
-            mouse_pos: Screen2d::from_logical([0.0, 0.0], hidpi_factor),
-            mouse_activity_start: Screen2d::from_logical([0.0, 0.0], hidpi_factor),
+            mouse_pos: Screen2d::zero(hidpi_factor),
+            mouse_activity_start: Screen2d::zero(hidpi_factor),
             mouse_in_window: false,
             mouse_left: MouseButtonState {
                 button: MouseButton::Left,
@@ -48,8 +81,39 @@ impl EventState {
             alt_down: false,
             ctrl_down: false,
             logo_down: false,
-            windows: vec![WindowData::new(win_id, win_dim, hidpi_factor)],
+            keys_down: BTreeSet::new(),
+            keys_just_pressed: BTreeSet::new(),
+            keys_just_released: BTreeSet::new(),
+            scancodes_down: BTreeSet::new(),
+            windows: vec![win_data],
+            wheel_total: Screen2d::zero(hidpi_factor),
+            wheel_line_total: [r32(0.0), r32(0.0)],
+            last_scroll: [r32(0.0), r32(0.0)],
+            raw_motion: [r32(0.0), r32(0.0)],
+            touches: BTreeMap::new(),
+            gestures: false,
+            last_activity: Instant::now(),
+            idle_includes_device_events: true,
+            dark_theme,
+            last_codepoint: None,
+            translate_cr: true,
             logical_line_height: r32(18.0),
+            forced_hidpi: None,
+            click_distance: r32(4.0),
+            drag_distance: r32(4.0),
+            double_click: false,
+            double_click_time: Duration::from_millis(500),
+            double_click_distance: r32(4.0),
+            pending: vec![initial_resize].into(),
+            frame_start: InputSnapshot {
+                mouse_pos: Screen2d::zero(hidpi_factor),
+                mouse_in_window: false,
+                mouse_left: false,
+                mouse_middle: false,
+                mouse_right: false,
+                modifiers: Modifiers::default(),
+                keys_down: BTreeSet::new(),
+            },
         }
     }
 
@@ -58,13 +122,92 @@ impl EventState {
             .iter()
             .nth(0)
             .map(|w| w.dim)
-            .unwrap_or(Screen2d::from_logical([0.0, 0.0], 1.0))
+            .unwrap_or(Screen2d::zero(1.0))
+    }
+    /// Dimensions of the given window, or None if the window is not being tracked.
+    pub fn win_dim(&self, id: crate::WindowId) -> Option<Screen2d> {
+        self.windows.iter().find(|w| w.id == id).map(|w| w.dim)
+    }
+    /// Indicates if the primary window is visible (not minimized). Rendering can be skipped while
+    /// the window is not visible (ex. by returning WindowState::Wait).
+    pub fn is_visible(&self) -> bool {
+        self.windows.first().map(|w| !w.minimized).unwrap_or(true)
+    }
+    /// Indicates if the given window is visible (not minimized), or None if the window is not
+    /// being tracked.
+    pub fn win_visible(&self, id: crate::WindowId) -> Option<bool> {
+        self.windows
+            .iter()
+            .find(|w| w.id == id)
+            .map(|w| !w.minimized)
+    }
+    /// HiDPI factor of the given window, or None if the window is not being tracked.
+    pub fn win_hidpi(&self, id: crate::WindowId) -> Option<f32> {
+        self.windows
+            .iter()
+            .find(|w| w.id == id)
+            .map(|w| self.forced_hidpi.unwrap_or(w.hidpi_factor).raw())
     }
 
     pub fn hidpi_factor(&self) -> f32 {
         self.hidpi_factor_r32().raw()
     }
+    /// Forces a specific hidpi factor to be used for all windows, regardless of the factor
+    /// reported by the operating system (ex. for a user-selected UI scale, or for tests that need
+    /// a deterministic factor). All Screen2d values created from events use the forced factor, and
+    /// the tracked window sizes and mouse position are rescaled to it. Use None to go back to the
+    /// factors reported by the operating system.
+    ///
+    /// Physical event coordinates still come from the operating system, so only the logical values
+    /// are affected (ex. a window that is 1600 physical pixels wide is 800 logical pixels wide with
+    /// a forced factor of 2.0). HiDpiFactorChanged events continue to report the system factor.
+    /// AsciiText, ShapeRenderer and SpriteBatch use the window's factor unless they are given the
+    /// same factor with set_hidpi_override.
+    pub fn set_forced_hidpi(&mut self, factor: Option<f32>) {
+        if let Some(f) = factor {
+            assert!(
+                f > 0.0 && f.is_finite(),
+                "HiDPI factor must be a positive finite number: {}",
+                f
+            );
+        }
+        self.forced_hidpi = factor.map(r32);
+        for i in 0..self.windows.len() {
+            let f = self
+                .forced_hidpi
+                .unwrap_or(self.windows[i].hidpi_factor)
+                .raw();
+            self.windows[i].dim = self.windows[i].dim.rescaled_physical(f);
+        }
+        let f = self.hidpi_factor();
+        self.mouse_pos = self.mouse_pos.rescaled_physical(f);
+        self.mouse_activity_start = self.mouse_activity_start.rescaled_physical(f);
+        for d in &mut [
+            &mut self.mouse_left,
+            &mut self.mouse_middle,
+            &mut self.mouse_right,
+        ] {
+            d.pressed_at = d.pressed_at.rescaled_physical(f);
+        }
+        for pos in self.touches.values_mut() {
+            *pos = pos.rescaled_physical(f);
+        }
+        self.wheel_total = self.wheel_total.rescaled_physical(f);
+    }
+    /// Forced hidpi factor (see set_forced_hidpi).
+    pub fn forced_hidpi(&self) -> Option<f32> {
+        self.forced_hidpi.map(|f| f.raw())
+    }
     pub(crate) fn hidpi_factor_r32(&self) -> R32 {
+        self.forced_hidpi
+            .unwrap_or_else(|| self.system_hidpi_factor_r32())
+    }
+    /// HiDPI factor used for the window's Screen2d values (the forced factor, if there is one).
+    pub(crate) fn win_hidpi_r32(&mut self, id: crate::WindowId) -> R32 {
+        let f = self.get_or_create_win(id).hidpi_factor;
+        self.forced_hidpi.unwrap_or(f)
+    }
+    fn system_hidpi_factor_r32(&self) -> R32 {
         self.windows
             .iter()
             .nth(0)
@@ -82,22 +225,104 @@ impl EventState {
             &mut self.windows[idx]
         } else {
             let idx = self.windows.len();
-            let hidpi_factor = self.hidpi_factor_r32();
+            let hidpi_factor = self.system_hidpi_factor_r32();
             self.windows.push(WindowData {
                 id,
                 dim: Screen2d::from_logical_r32([r32(0.0), r32(0.0)], hidpi_factor),
                 hidpi_factor,
+                minimized: false,
             });
             &mut self.windows[idx]
         }
     }
+    /// Starts tracking the dimensions and hidpi factor of another window. This is only needed for
+    /// windows that were not created by Window::create_additional.
+    /// An initial WindowResize event is sent for the window.
+    pub fn add_window(&mut self, display: &glium::Display) {
+        let win_data = self.window_data(display);
+        self.window_destroyed(win_data.id);
+        self.push_window_event(PendingEvent::WindowResize {
+            win_id: win_data.id,
+            size: win_data.dim,
+        });
+        self.windows.push(win_data);
+    }
+    /// Updates the tracked window after the display was rebuilt (which creates a new window). A
+    /// WindowResize event is sent for the new window.
+    pub(crate) fn replace_window(&mut self, old_id: crate::WindowId, display: &glium::Display) {
+        let win_data = self.window_data(display);
+        self.push_window_event(PendingEvent::WindowResize {
+            win_id: win_data.id,
+            size: win_data.dim,
+        });
+        match self.windows.iter_mut().find(|w| w.id == old_id) {
+            Some(w) => *w = win_data,
+            None => self.windows.push(win_data),
+        }
+    }
+    fn window_data(&self, display: &glium::Display) -> WindowData {
+        let mut win_data = WindowData::from_display(display);
+        if let Some(f) = self.forced_hidpi {
+            win_data.dim = win_data.dim.rescaled_physical(f.raw());
+        }
+        win_data
+    }
+    /// Queues a window event, replacing an undelivered event of the same kind for the window.
+    pub(crate) fn push_window_event(&mut self, e: PendingEvent) {
+        self.pending.retain(|p| !p.is_replaced_by(&e));
+        self.pending.push_back(e);
+    }
+    /// Drops the input events that were generated for the previous event but never taken with
+    /// next_pending_event.
+    pub(crate) fn discard_stale_input(&mut self) {
+        self.pending.retain(|e| !e.is_input());
+    }
     pub(crate) fn window_destroyed(&mut self, id: crate::WindowId) {
         self.windows.retain(|w| w.id != id);
     }
 
+    /// Clears the state of the mouse buttons, modifier keys, pressed keys, touches and mouse wheel
+    /// (ex. when switching between scenes). The window information, mouse position and settings
+    /// (such as the double-click time) are preserved.
+    pub fn reset_input(&mut self) {
+        for d in &mut [
+            &mut self.mouse_left,
+            &mut self.mouse_middle,
+            &mut self.mouse_right,
+        ] {
+            **d = MouseButtonState {
+                button: d.button.clone(),
+                ..Default::default()
+            };
+        }
+        self.mouse_activity_start = self.mouse_pos;
+        self.shift_down = false;
+        self.alt_down = false;
+        self.ctrl_down = false;
+        self.logo_down = false;
+        self.keys_down.clear();
+        self.keys_just_pressed.clear();
+        self.keys_just_released.clear();
+        self.scancodes_down.clear();
+        self.touches.clear();
+        self.raw_motion = [r32(0.0), r32(0.0)];
+        self.double_click = false;
+        // Window events (ex. the initial WindowResize) are still delivered
+        self.discard_stale_input();
+        self.reset_wheel();
+    }
+
+    /// Height of a line of text in logical pixels (the default is 18.0). This is used to convert
+    /// between the two kinds of mouse wheel deltas: line deltas (from a mouse wheel) are multiplied
+    /// by it to get MouseWheel::delta, and pixel deltas (from a trackpad) are divided by it to get
+    /// MouseWheel::scroll. It should match the line height of the scrolled content, so that one
+    /// notch of the mouse wheel scrolls by one line and both kinds of devices scroll at a similar
+    /// speed.
     pub fn logical_line_height(&self) -> f32 {
         self.logical_line_height.raw()
     }
+    /// Sets the line height in logical pixels (see logical_line_height). For text drawn using
+    /// AsciiText, this is 9.0 times the text scale (or TextStyle::line_height).
     pub fn set_logical_line_height(&mut self, h: f32) {
         assert!(
             h >= 1.0,
@@ -105,6 +330,198 @@ impl EventState {
         );
         self.logical_line_height = r32(h);
     }
+    /// Sets the line height from a size in typographic points (ex. the line height of a font
+    /// specified in points). A point is 1/72 of an inch and a logical pixel is 1/96 of an inch, so a
+    /// 12pt line is 16 logical pixels high.
+    pub fn set_line_height_from_points(&mut self, pt: f32) {
+        self.set_logical_line_height(pt * 96.0 / 72.0);
+    }
+
+    /// Current state of the modifier keys (shift_down, ctrl_down, alt_down and logo_down).
+    pub fn modifiers(&self) -> Modifiers {
+        Modifiers {
+            shift: self.shift_down,
+            ctrl: self.ctrl_down,
+            alt: self.alt_down,
+            logo: self.logo_down,
+        }
+    }
+
+    /// Indicates if the given key is currently held down.
+    pub fn is_key_down(&self, vkey: VirtualKeyCode) -> bool {
+        self.keys_down.contains(&vkey)
+    }
+    /// Indicates if the key was pressed since the last call to end_frame (auto-repeats are not
+    /// included).
+    pub fn is_key_just_pressed(&self, vkey: VirtualKeyCode) -> bool {
+        self.keys_just_pressed.contains(&vkey)
+    }
+    /// Indicates if the key was released since the last call to end_frame. Keys that are held down
+    /// when the window loses focus count as released.
+    pub fn is_key_just_released(&self, vkey: VirtualKeyCode) -> bool {
+        self.keys_just_released.contains(&vkey)
+    }
+    /// Indicates if the key with the given scan code is currently held down.
+    pub fn is_scancode_down(&self, code: ScanCode) -> bool {
+        self.scancodes_down.contains(&code)
+    }
+    /// All keys that are currently held down (keys without a virtual key code are only available
+    /// via pressed_scancodes).
+    pub fn pressed_keys(&self) -> impl Iterator<Item = VirtualKeyCode> + '_ {
+        self.keys_down.iter().copied()
+    }
+    /// Scan codes of all keys that are currently held down.
+    pub fn pressed_scancodes(&self) -> impl Iterator<Item = ScanCode> + '_ {
+        self.scancodes_down.iter().copied()
+    }
+
+    /// Current input state as a single value (ex. for game systems that poll input once per frame
+    /// rather than handling individual events).
+    pub fn snapshot(&self) -> InputSnapshot {
+        InputSnapshot {
+            mouse_pos: self.mouse_pos,
+            mouse_in_window: self.mouse_in_window,
+            mouse_left: self.mouse_left.pressed,
+            mouse_middle: self.mouse_middle.pressed,
+            mouse_right: self.mouse_right.pressed,
+            modifiers: self.modifiers(),
+            keys_down: self.keys_down.clone(),
+        }
+    }
+    /// Marks the end of a frame by recording the current input state (see frame_start) and
+    /// clearing the just pressed and just released states of the keys and mouse buttons. Call this
+    /// once per frame after the input has been processed.
+    pub fn end_frame(&mut self) {
+        self.frame_start = self.snapshot();
+        self.keys_just_pressed.clear();
+        self.keys_just_released.clear();
+        for d in &mut [
+            &mut self.mouse_left,
+            &mut self.mouse_middle,
+            &mut self.mouse_right,
+        ] {
+            d.just_pressed = false;
+            d.just_released = false;
+        }
+    }
+    /// Input state at the last call to end_frame. Compare this against the current snapshot to
+    /// find what changed during the frame (a key that is pressed and released within the same
+    /// frame does not show up as a change).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let input = event_state.snapshot();
+    /// if input.key_pressed_since(event_state.frame_start(), glu::VirtualKeyCode::Space) {
+    ///     // Jump...
+    /// }
+    /// event_state.end_frame();
+    /// ```
+    pub fn frame_start(&self) -> &InputSnapshot {
+        &self.frame_start
+    }
+
+    /// Maximum distance (in logical units) the mouse can move between pressing and releasing a
+    /// button for it to count as a click (the default is 4.0).
+    pub fn set_click_distance(&mut self, dist: f32) {
+        self.click_distance = r32(dist);
+    }
+    /// Minimum distance (in logical units) the mouse must move while a button is pressed before a
+    /// DragStart event is sent (the default is 4.0).
+    pub fn set_drag_distance(&mut self, dist: f32) {
+        self.drag_distance = r32(dist);
+    }
+    /// Fingers that are currently touching the screen, along with their most recent position.
+    pub fn active_touches(&self) -> impl Iterator<Item = (FingerId, Screen2d)> + '_ {
+        self.touches.iter().map(|(id, pos)| (*id, *pos))
+    }
+    /// Position of the given finger, or None if it is not touching the screen.
+    pub fn touch_pos(&self, finger: FingerId) -> Option<Screen2d> {
+        self.touches.get(&finger).copied()
+    }
+
+    /// Time since the last window or device event (ex. input, resizing, or focus changes). Redraw,
+    /// user, and application events do not count as activity.
+    pub fn idle_time(&self) -> Duration {
+        self.last_activity.elapsed()
+    }
+    /// Sets if device events (ex. MouseMotion, which is also sent while the mouse is outside of
+    /// the window) reset the idle time (the default is true).
+    pub fn set_idle_includes_device_events(&mut self, include: bool) {
+        self.idle_includes_device_events = include;
+    }
+
+    /// Sets if a carriage return ('\r', which is what the enter key produces on some platforms) is
+    /// reported as a new line in the ch field of KeyText events (the default is true). When
+    /// enabled, a "\r\n" sequence produces a single new line. When disabled, '\r' is passed
+    /// through unchanged.
+    pub fn set_translate_cr(&mut self, translate: bool) {
+        self.translate_cr = translate;
+    }
+
+    /// Indicates if the system is using a dark theme. This is only known at startup on Windows,
+    /// and on other platforms is false until a ThemeChanged event is received.
+    pub fn is_dark_theme(&self) -> bool {
+        self.dark_theme
+    }
+
+    /// Enables the Pinch, Rotate and Pan events, which are generated from the Touch events (this is
+    /// disabled by default).
+    pub fn set_gestures_enabled(&mut self, enabled: bool) {
+        self.gestures = enabled;
+    }
+    /// Indicates if the most recent mouse button press was the second click of a double-click.
+    pub fn is_double_click(&self) -> bool {
+        self.double_click
+    }
+    /// Maximum amount of time between two clicks for them to count as a double-click (the default
+    /// is 500 milliseconds).
+    pub fn set_double_click_time(&mut self, d: Duration) {
+        self.double_click_time = d;
+    }
+    /// Maximum distance (in logical units) the mouse can move between two clicks for them to count
+    /// as a double-click (the default is 4.0).
+    pub fn set_double_click_distance(&mut self, dist: f32) {
+        self.double_click_distance = r32(dist);
+    }
+
+    /// Total mouse wheel movement (in logical units) since the start of the application or the
+    /// last call to reset_wheel.
+    pub fn wheel_total(&self) -> Screen2d {
+        self.wheel_total
+    }
+    /// Total mouse wheel movement in lines since the start of the application or the last call to
+    /// reset_wheel. This only includes wheel events that were reported in lines.
+    pub fn wheel_line_total(&self) -> [f32; 2] {
+        [
+            self.wheel_line_total[0].raw(),
+            self.wheel_line_total[1].raw(),
+        ]
+    }
+    /// Horizontal and vertical scroll amounts of the most recent MouseWheel event, in approximate
+    /// mouse wheel notches (see MouseWheel::scroll). Positive values scroll right and away from
+    /// the user. Horizontal scrolling comes from tilt wheels and trackpads, and should not be
+    /// ignored for wide content (ex. timelines).
+    pub fn scroll_axes(&self) -> (f32, f32) {
+        (self.last_scroll[0].raw(), self.last_scroll[1].raw())
+    }
+    /// Resets the wheel_total and wheel_line_total values to zero (ex. when a zoomable view is
+    /// reset to its default zoom, or when switching to content with its own scroll position).
+    pub fn reset_wheel(&mut self) {
+        self.wheel_total = Screen2d::zero(self.hidpi_factor());
+        self.wheel_line_total = [r32(0.0), r32(0.0)];
+    }
+    /// Returns the raw mouse movement (from MouseMotion events) accumulated since the last call,
+    /// and resets it to zero. Call this once per frame for camera controls (ex. first-person
+    /// mouse look). The values are in device units and are not affected by pointer acceleration.
+    ///
+    /// This is only meaningful while the cursor is grabbed (see DisplayExt::set_cursor_grab), since
+    /// otherwise the mouse also moves the cursor (and may leave the window).
+    pub fn take_raw_motion(&mut self) -> [f32; 2] {
+        let [dx, dy] = self.raw_motion;
+        self.raw_motion = [r32(0.0), r32(0.0)];
+        [dx.raw(), dy.raw()]
+    }
 
     pub fn is_any_mouse_button_pressed(&self) -> bool {
         self.mouse_left.pressed || self.mouse_middle.pressed || self.mouse_right.pressed
@@ -121,9 +538,16 @@ impl EventState {
         }
         None
     }
-    pub fn process_event<T>(&mut self, evt: &gl::event::Event<T>) -> Event {
+    pub fn process_event<T: Clone>(&mut self, evt: &gl::event::Event<T>) -> Event<T> {
         Event::from_gl(evt, self)
     }
+    /// Returns the next event generated by the event state itself (ex. MouseDoubleClick, DragStart,
+    /// Pinch or the initial WindowResize). These should be handled after the event returned by
+    /// process_event, before the next event is processed (input events that are not taken by then
+    /// are discarded). This is done automatically when using Window::run.
+    pub fn next_pending_event<T>(&mut self) -> Option<Event<T>> {
+        self.pending.pop_front().map(|e| e.into_event())
+    }
     pub fn get_mouse_drag_dist(&self) -> Option<Screen2d> {
         if let Some(start) = self.get_mouse_pressed_at() {
             Some(self.mouse_pos - start)
@@ -131,6 +555,13 @@ impl EventState {
             None
         }
     }
+    /// Indicates if the mouse has moved more than the given distance (in logical units) from where
+    /// the mouse button was pressed. This is always false when no mouse button is pressed.
+    pub fn drag_exceeds(&self, threshold: f32) -> bool {
+        self.get_mouse_drag_dist()
+            .map(|d| d.length() > threshold)
+            .unwrap_or(false)
+    }
     /// Distance from the last mouse click (left button).
     pub fn mouse_left_dist(&self) -> Screen2d {
         self.mouse_pos - self.mouse_left.pressed_at
@@ -156,16 +587,84 @@ pub struct MouseButtonState {
     pub pressed_at: Screen2d,
     /// Indicates if the user pressed escape while the mouse button was down.
     pub cancelled: bool,
+    /// Time the mouse button was last pressed (used to detect double-clicks).
+    pub pressed_time: Option<Instant>,
+    /// Indicates if the mouse is being dragged with this button held down.
+    pub dragging: bool,
+    /// Indicates if the button was pressed since the last call to EventState::end_frame.
+    pub just_pressed: bool,
+    /// Indicates if the button was released since the last call to EventState::end_frame.
+    pub just_released: bool,
 }
 impl Default for MouseButtonState {
     fn default() -> MouseButtonState {
         MouseButtonState {
             button: MouseButton::Left,
             pressed: false,
-            pressed_at: Screen2d::from_logical([0.0, 0.0], 1.0),
+            pressed_at: Screen2d::zero(1.0),
             cancelled: false,
+            pressed_time: None,
+            dragging: false,
+            just_pressed: false,
+            just_released: false,
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn initial_dark_theme(display: &glium::Display) -> bool {
+    use gl::platform::windows::WindowExtWindows;
+    display.gl_window().window().theme() == gl::window::Theme::Dark
+}
+#[cfg(not(target_os = "windows"))]
+fn initial_dark_theme(_display: &glium::Display) -> bool {
+    false
+}
+
+/// Input state at a point in time (see EventState::snapshot).
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct InputSnapshot {
+    pub mouse_pos: Screen2d,
+    pub mouse_in_window: bool,
+    /// Indicates if the left mouse button is held down.
+    pub mouse_left: bool,
+    pub mouse_middle: bool,
+    pub mouse_right: bool,
+    pub modifiers: Modifiers,
+    pub keys_down: BTreeSet<VirtualKeyCode>,
+}
+impl InputSnapshot {
+    pub fn is_key_down(&self, vkey: VirtualKeyCode) -> bool {
+        self.keys_down.contains(&vkey)
+    }
+    /// Indicates if the mouse button is held down (other buttons are not tracked).
+    pub fn is_button_down(&self, button: &MouseButton) -> bool {
+        match button {
+            MouseButton::Left => self.mouse_left,
+            MouseButton::Middle => self.mouse_middle,
+            MouseButton::Right => self.mouse_right,
+            MouseButton::Other(_) => false,
         }
     }
+
+    /// Indicates if the key is down now but was not down in the earlier snapshot.
+    pub fn key_pressed_since(&self, earlier: &InputSnapshot, vkey: VirtualKeyCode) -> bool {
+        self.is_key_down(vkey) && !earlier.is_key_down(vkey)
+    }
+    /// Indicates if the key was down in the earlier snapshot but is not down now.
+    pub fn key_released_since(&self, earlier: &InputSnapshot, vkey: VirtualKeyCode) -> bool {
+        !self.is_key_down(vkey) && earlier.is_key_down(vkey)
+    }
+    pub fn button_pressed_since(&self, earlier: &InputSnapshot, button: &MouseButton) -> bool {
+        self.is_button_down(button) && !earlier.is_button_down(button)
+    }
+    pub fn button_released_since(&self, earlier: &InputSnapshot, button: &MouseButton) -> bool {
+        !self.is_button_down(button) && earlier.is_button_down(button)
+    }
+    /// Distance the mouse moved since the earlier snapshot.
+    pub fn mouse_delta_since(&self, earlier: &InputSnapshot) -> Screen2d {
+        self.mouse_pos - earlier.mouse_pos
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
@@ -173,6 +672,7 @@ pub struct WindowData {
     pub id: crate::WindowId,
     pub dim: Screen2d,
     pub(crate) hidpi_factor: R32,
+    pub(crate) minimized: bool,
 }
 impl WindowData {
     pub fn new(id: crate::WindowId, dim: Screen2d, hidpi_factor: f32) -> Self {
@@ -182,9 +682,497 @@ impl WindowData {
             id,
             dim,
             hidpi_factor,
+            minimized: false,
         }
     }
+    pub fn from_display(display: &glium::Display) -> Self {
+        use glium::backend::Facade;
+        let hidpi_factor = display.gl_window().window().scale_factor() as f32;
+        let win_id = display.gl_window().window().id();
+        let win_dim = display.get_context().get_framebuffer_dimensions();
+        let win_dim = Screen2d::from_physical_u32([win_dim.0, win_dim.1], hidpi_factor);
+        Self::new(win_id, win_dim, hidpi_factor)
+    }
     pub fn hidpi_factor(&self) -> f32 {
         self.hidpi_factor.raw()
     }
+    /// Indicates if the window is currently minimized.
+    pub fn is_minimized(&self) -> bool {
+        self.minimized
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use glium::glutin::event::DeviceId;
+    use glium::glutin::window::WindowId;
+
+    fn test_state() -> EventState {
+        let win_id = unsafe { WindowId::dummy() };
+        let dim = Screen2d::from_physical_u32([800, 600], 1.0);
+        EventState::with_window(WindowData::new(win_id, dim, 1.0), false)
+    }
+
+    #[test]
+    fn raw_motion_accumulates_until_taken() {
+        let mut state = test_state();
+        for delta in &[(3.0, -1.0), (2.5, 0.0), (-0.5, 4.0)] {
+            state.process_event::<()>(&gl::event::Event::DeviceEvent {
+                device_id: unsafe { DeviceId::dummy() },
+                event: gl::event::DeviceEvent::MouseMotion { delta: *delta },
+            });
+        }
+        assert_eq!(state.take_raw_motion(), [5.0, 3.0]);
+        assert_eq!(state.take_raw_motion(), [0.0, 0.0]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn horizontal_scroll_is_kept() {
+        let mut state = test_state();
+        let e = state.process_event::<()>(&gl::event::Event::WindowEvent {
+            window_id: unsafe { WindowId::dummy() },
+            event: gl::event::WindowEvent::MouseWheel {
+                device_id: unsafe { DeviceId::dummy() },
+                delta: gl::event::MouseScrollDelta::LineDelta(2.0, 0.0),
+                phase: gl::event::TouchPhase::Moved,
+                modifiers: Default::default(),
+            },
+        });
+        match e {
+            Event::MouseWheel {
+                delta, delta_line, ..
+            } => {
+                assert_eq!(delta_line, Some([2.0, 0.0]));
+                assert_eq!(delta.logical(), [2.0 * 18.0, 0.0]);
+            }
+            _ => panic!("Expected a MouseWheel event"),
+        }
+        assert_eq!(state.scroll_axes(), (2.0, 0.0));
+        assert_eq!(state.wheel_line_total(), [2.0, 0.0]);
+    }
+
+    #[test]
+    fn reset_input_keeps_initial_resize() {
+        let mut state = test_state();
+        state.reset_input();
+        match state.next_pending_event::<()>() {
+            Some(Event::WindowResize { size, .. }) => assert_eq!(size.physical(), [800, 600]),
+            e => panic!("Expected a WindowResize event: {:?}", e),
+        }
+        assert!(state.next_pending_event::<()>().is_none());
+    }
+
+    fn window_event(state: &mut EventState, event: gl::event::WindowEvent) -> Event<()> {
+        state.process_event(&gl::event::Event::WindowEvent {
+            window_id: unsafe { WindowId::dummy() },
+            event,
+        })
+    }
+
+    #[allow(deprecated)]
+    fn move_mouse(state: &mut EventState, pos: [f64; 2]) {
+        window_event(
+            state,
+            gl::event::WindowEvent::CursorMoved {
+                device_id: unsafe { DeviceId::dummy() },
+                position: gl::dpi::PhysicalPosition::new(pos[0], pos[1]),
+                modifiers: Default::default(),
+            },
+        );
+    }
+
+    #[allow(deprecated)]
+    fn mouse_input(state: &mut EventState, pressed: bool) {
+        let element_state = if pressed {
+            gl::event::ElementState::Pressed
+        } else {
+            gl::event::ElementState::Released
+        };
+        window_event(
+            state,
+            gl::event::WindowEvent::MouseInput {
+                device_id: unsafe { DeviceId::dummy() },
+                state: element_state,
+                button: gl::event::MouseButton::Left,
+                modifiers: Default::default(),
+            },
+        );
+    }
+
+    #[test]
+    fn pending_queue_is_bounded_without_draining() {
+        let mut state = test_state();
+        for i in 0..100 {
+            let x = (i % 10) as f64 * 50.0;
+            move_mouse(&mut state, [x, 100.0]);
+            mouse_input(&mut state, true);
+            move_mouse(&mut state, [x + 40.0, 140.0]);
+            move_mouse(&mut state, [x + 80.0, 180.0]);
+            mouse_input(&mut state, false);
+            assert!(state.pending.len() <= 3, "{:?}", state.pending);
+        }
+        // The initial resize is still delivered, followed by the events of the last release
+        match state.next_pending_event() {
+            Some(Event::<()>::WindowResize { .. }) => {}
+            e => panic!("Expected a WindowResize event: {:?}", e),
+        }
+        match state.next_pending_event() {
+            Some(Event::<()>::DragEnd { cancelled, .. }) => assert!(!cancelled),
+            e => panic!("Expected a DragEnd event: {:?}", e),
+        }
+        assert!(state.next_pending_event::<()>().is_none());
+    }
+
+    fn drain(state: &mut EventState) -> Vec<Event<()>> {
+        std::iter::from_fn(|| state.next_pending_event()).collect()
+    }
+
+    fn is_double_click(e: &Event<()>) -> bool {
+        matches!(
+            e,
+            Event::MouseDoubleClick {
+                button: MouseButton::Left,
+                ..
+            }
+        )
+    }
+
+    #[test]
+    fn double_click() {
+        let mut state = test_state();
+        move_mouse(&mut state, [100.0, 100.0]);
+        drain(&mut state);
+        mouse_input(&mut state, true);
+        assert!(!state.is_double_click());
+        mouse_input(&mut state, false);
+        drain(&mut state);
+        move_mouse(&mut state, [102.0, 101.0]);
+        mouse_input(&mut state, true);
+        assert!(state.is_double_click());
+        let events = drain(&mut state);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            Event::MouseDoubleClick { pos, .. } => assert_eq!(pos.logical(), [102.0, 101.0]),
+            e => panic!("Expected a MouseDoubleClick event: {:?}", e),
+        }
+        // A third click starts over instead of counting as another double-click
+        mouse_input(&mut state, false);
+        mouse_input(&mut state, true);
+        assert!(!state.is_double_click());
+        assert!(!drain(&mut state).iter().any(is_double_click));
+    }
+
+    #[test]
+    fn double_click_requires_nearby_press() {
+        let mut state = test_state();
+        move_mouse(&mut state, [100.0, 100.0]);
+        mouse_input(&mut state, true);
+        mouse_input(&mut state, false);
+        move_mouse(&mut state, [110.0, 100.0]);
+        mouse_input(&mut state, true);
+        assert!(!state.is_double_click());
+        assert!(!drain(&mut state).iter().any(is_double_click));
+    }
+
+    fn clicks(events: &[Event<()>]) -> Vec<[f32; 2]> {
+        events
+            .iter()
+            .filter_map(|e| match e {
+                Event::MouseClick {
+                    button: MouseButton::Left,
+                    pos,
+                    ..
+                } => Some(pos.logical()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn click_on_release_near_press() {
+        let mut state = test_state();
+        move_mouse(&mut state, [100.0, 100.0]);
+        mouse_input(&mut state, true);
+        assert!(clicks(&drain(&mut state)).is_empty());
+        // Small movements while pressed still count as a click
+        move_mouse(&mut state, [103.0, 100.0]);
+        mouse_input(&mut state, false);
+        assert_eq!(clicks(&drain(&mut state)), vec![[103.0, 100.0]]);
+    }
+
+    #[test]
+    fn no_click_after_moving_away() {
+        let mut state = test_state();
+        move_mouse(&mut state, [100.0, 100.0]);
+        mouse_input(&mut state, true);
+        move_mouse(&mut state, [110.0, 100.0]);
+        mouse_input(&mut state, false);
+        assert!(clicks(&drain(&mut state)).is_empty());
+        // Even when the mouse is moved back before releasing
+        mouse_input(&mut state, true);
+        move_mouse(&mut state, [130.0, 100.0]);
+        move_mouse(&mut state, [110.0, 100.0]);
+        mouse_input(&mut state, false);
+        assert!(clicks(&drain(&mut state)).is_empty());
+    }
+
+    #[allow(deprecated)]
+    fn press_escape(state: &mut EventState) {
+        window_event(
+            state,
+            gl::event::WindowEvent::KeyboardInput {
+                device_id: unsafe { DeviceId::dummy() },
+                input: gl::event::KeyboardInput {
+                    scancode: 1,
+                    state: gl::event::ElementState::Pressed,
+                    virtual_keycode: Some(VirtualKeyCode::Escape),
+                    modifiers: Default::default(),
+                },
+                is_synthetic: false,
+            },
+        );
+    }
+
+    #[test]
+    fn drag_start_move_end() {
+        let mut state = test_state();
+        move_mouse(&mut state, [100.0, 100.0]);
+        mouse_input(&mut state, true);
+        drain(&mut state);
+        // Within the drag distance
+        move_mouse(&mut state, [102.0, 100.0]);
+        assert!(drain(&mut state).is_empty());
+        move_mouse(&mut state, [110.0, 100.0]);
+        match drain(&mut state).as_slice() {
+            [Event::DragStart { from, button, .. }] => {
+                assert_eq!(from.logical(), [100.0, 100.0]);
+                assert_eq!(*button, MouseButton::Left);
+            }
+            e => panic!("Expected a DragStart event: {:?}", e),
+        }
+        move_mouse(&mut state, [115.0, 98.0]);
+        match drain(&mut state).as_slice() {
+            [Event::DragMove {
+                from, to, delta, ..
+            }] => {
+                assert_eq!(from.logical(), [100.0, 100.0]);
+                assert_eq!(to.logical(), [115.0, 98.0]);
+                assert_eq!(delta.logical(), [5.0, -2.0]);
+            }
+            e => panic!("Expected a DragMove event: {:?}", e),
+        }
+        mouse_input(&mut state, false);
+        match drain(&mut state).as_slice() {
+            [Event::DragEnd {
+                from,
+                to,
+                cancelled,
+                ..
+            }] => {
+                assert_eq!(from.logical(), [100.0, 100.0]);
+                assert_eq!(to.logical(), [115.0, 98.0]);
+                assert!(!cancelled);
+            }
+            e => panic!("Expected only a DragEnd event: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn escape_cancels_drag() {
+        let mut state = test_state();
+        move_mouse(&mut state, [100.0, 100.0]);
+        mouse_input(&mut state, true);
+        move_mouse(&mut state, [120.0, 100.0]);
+        drain(&mut state);
+        press_escape(&mut state);
+        match drain(&mut state).as_slice() {
+            [Event::DragEnd { to, cancelled, .. }] => {
+                assert_eq!(to.logical(), [120.0, 100.0]);
+                assert!(cancelled);
+            }
+            e => panic!("Expected a cancelled DragEnd event: {:?}", e),
+        }
+        // The button stays cancelled until it is released: no further drag or click events
+        move_mouse(&mut state, [130.0, 100.0]);
+        move_mouse(&mut state, [100.0, 100.0]);
+        mouse_input(&mut state, false);
+        assert!(drain(&mut state).is_empty());
+        assert!(!state.mouse_left.dragging && !state.mouse_left.pressed);
+    }
+
+    #[test]
+    fn escape_cancels_click() {
+        let mut state = test_state();
+        move_mouse(&mut state, [100.0, 100.0]);
+        mouse_input(&mut state, true);
+        drain(&mut state);
+        press_escape(&mut state);
+        mouse_input(&mut state, false);
+        assert!(drain(&mut state).is_empty());
+    }
+
+    fn typed_char(state: &mut EventState, codepoint: char) -> Option<char> {
+        match window_event(state, gl::event::WindowEvent::ReceivedCharacter(codepoint)) {
+            Event::KeyText { ch, .. } => ch,
+            e => panic!("Expected a KeyText event: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn altgr_text_is_kept() {
+        use gl::event::ModifiersState;
+        let mut state = test_state();
+        let ctrl_alt = ModifiersState::CTRL | ModifiersState::ALT;
+        window_event(
+            &mut state,
+            gl::event::WindowEvent::ModifiersChanged(ctrl_alt),
+        );
+        assert_eq!(typed_char(&mut state, '@'), Some('@'));
+        assert_eq!(typed_char(&mut state, '\u{20ac}'), Some('\u{20ac}'));
+        // Ctrl on its own is a shortcut
+        let ctrl = ModifiersState::CTRL;
+        window_event(&mut state, gl::event::WindowEvent::ModifiersChanged(ctrl));
+        assert_eq!(typed_char(&mut state, 's'), None);
+        assert_eq!(typed_char(&mut state, '\u{e9}'), None);
+        let none = ModifiersState::empty();
+        window_event(&mut state, gl::event::WindowEvent::ModifiersChanged(none));
+        assert_eq!(typed_char(&mut state, 's'), Some('s'));
+    }
+
+    fn touch(state: &mut EventState, finger: u64, phase: gl::event::TouchPhase, pos: [f64; 2]) {
+        window_event(
+            state,
+            gl::event::WindowEvent::Touch(gl::event::Touch {
+                device_id: unsafe { DeviceId::dummy() },
+                phase,
+                location: gl::dpi::PhysicalPosition::new(pos[0], pos[1]),
+                force: None,
+                id: finger,
+            }),
+        );
+    }
+
+    /// State with gesture detection enabled and two fingers at (100, 100) and (200, 100).
+    fn two_finger_state() -> EventState {
+        let mut state = test_state();
+        state.set_gestures_enabled(true);
+        touch(
+            &mut state,
+            1,
+            gl::event::TouchPhase::Started,
+            [100.0, 100.0],
+        );
+        touch(
+            &mut state,
+            2,
+            gl::event::TouchPhase::Started,
+            [200.0, 100.0],
+        );
+        drain(&mut state);
+        state
+    }
+
+    #[test]
+    fn pinch_gesture() {
+        let mut state = two_finger_state();
+        touch(&mut state, 2, gl::event::TouchPhase::Moved, [300.0, 100.0]);
+        match drain(&mut state).as_slice() {
+            [Event::Pinch {
+                scale_delta,
+                center,
+                ..
+            }, Event::Pan { delta, .. }] => {
+                assert_eq!(*scale_delta, 2.0);
+                assert_eq!(center.logical(), [200.0, 100.0]);
+                assert_eq!(delta.logical(), [50.0, 0.0]);
+            }
+            e => panic!("Expected Pinch and Pan events: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn rotate_gesture_sign() {
+        // Moving the second finger from the right of the first to below it is clockwise
+        let mut state = two_finger_state();
+        touch(&mut state, 2, gl::event::TouchPhase::Moved, [100.0, 200.0]);
+        match drain(&mut state).as_slice() {
+            [Event::Rotate { angle_delta, .. }, Event::Pan { .. }] => {
+                assert!((angle_delta - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+            }
+            e => panic!("Expected Rotate and Pan events: {:?}", e),
+        }
+        let mut state = two_finger_state();
+        touch(&mut state, 2, gl::event::TouchPhase::Moved, [100.0, 0.0]);
+        match drain(&mut state).as_slice() {
+            [Event::Rotate { angle_delta, .. }, Event::Pan { .. }] => {
+                assert!((angle_delta + std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+            }
+            e => panic!("Expected Rotate and Pan events: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn lifting_a_finger_falls_back_to_pan() {
+        let mut state = two_finger_state();
+        touch(&mut state, 1, gl::event::TouchPhase::Ended, [100.0, 100.0]);
+        assert!(drain(&mut state).is_empty());
+        touch(&mut state, 2, gl::event::TouchPhase::Moved, [210.0, 95.0]);
+        match drain(&mut state).as_slice() {
+            [Event::Pan { delta, center, .. }] => {
+                assert_eq!(delta.logical(), [10.0, -5.0]);
+                assert_eq!(center.logical(), [210.0, 95.0]);
+            }
+            e => panic!("Expected a Pan event: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn third_finger_is_ignored() {
+        let mut state = two_finger_state();
+        touch(
+            &mut state,
+            3,
+            gl::event::TouchPhase::Started,
+            [500.0, 500.0],
+        );
+        touch(&mut state, 3, gl::event::TouchPhase::Moved, [400.0, 300.0]);
+        assert!(drain(&mut state).is_empty());
+        // The first two fingers still form the gesture
+        touch(&mut state, 2, gl::event::TouchPhase::Moved, [150.0, 100.0]);
+        match drain(&mut state).as_slice() {
+            [Event::Pinch { scale_delta, .. }, Event::Pan { .. }] => assert_eq!(*scale_delta, 0.5),
+            e => panic!("Expected Pinch and Pan events: {:?}", e),
+        }
+    }
+
+    #[test]
+    fn gestures_are_disabled_by_default() {
+        let mut state = two_finger_state();
+        state.set_gestures_enabled(false);
+        touch(&mut state, 2, gl::event::TouchPhase::Moved, [300.0, 200.0]);
+        assert!(drain(&mut state).is_empty());
+        assert!(!test_state().gestures);
+    }
+
+    #[test]
+    fn forced_hidpi_matches_renderer_transform() {
+        use crate::ascii_text::framebuffer_transform;
+        use crate::screen_units::screen_ortho_logical;
+
+        let win_id = unsafe { WindowId::dummy() };
+        let dim = Screen2d::from_physical_u32([1200, 900], 1.5);
+        let mut state = EventState::with_window(WindowData::new(win_id, dim, 1.5), false);
+        assert_eq!(
+            screen_ortho_logical(state.primary_win_dim()),
+            framebuffer_transform([1200, 900], 1.5)
+        );
+        state.set_forced_hidpi(Some(2.0));
+        assert_eq!(state.primary_win_dim().logical(), [600.0, 450.0]);
+        assert_eq!(
+            screen_ortho_logical(state.primary_win_dim()),
+            framebuffer_transform([1200, 900], state.forced_hidpi().unwrap())
+        );
+    }
 }
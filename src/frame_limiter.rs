@@ -0,0 +1,144 @@
+use crate::time_step::{Clock, SystemClock};
+use crate::window::WindowState;
+use std::time::{Duration, Instant};
+
+/// Limits how often frames are rendered (ex. 60 frames per second on a 144 Hz display, or when
+/// vsync is disabled). Frames are scheduled at a fixed cadence from the first frame, so small
+/// delays do not accumulate into drift.
+///
+/// # Examples
+///
+/// ```
+/// let mut limiter = glu::FrameLimiter::new(60.0);
+/// win.run(move |display, e, event_state| match e {
+///     glu::Event::Redraw if limiter.is_frame_due() => {
+///         // Render window...
+///         limiter.frame_end()
+///     }
+///     _ => limiter.wait(),
+/// });
+/// ```
+pub struct FrameLimiter<C: Clock = SystemClock> {
+    clock: C,
+    frame_time: Duration,
+    next_frame: Option<Instant>,
+}
+
+impl FrameLimiter {
+    /// Creates a new frame limiter for the given number of frames per second.
+    pub fn new(fps: f64) -> Self {
+        Self::with_clock(fps, SystemClock)
+    }
+}
+
+impl<C: Clock> FrameLimiter<C> {
+    /// Creates a new frame limiter for the given number of frames per second, using the given
+    /// clock to measure time (ex. a ManualClock in tests).
+    pub fn with_clock(fps: f64, clock: C) -> Self {
+        Self {
+            clock,
+            frame_time: frame_time(fps),
+            next_frame: None,
+        }
+    }
+    /// Changes the target number of frames per second.
+    pub fn set_fps(&mut self, fps: f64) {
+        self.frame_time = frame_time(fps);
+    }
+
+    /// Indicates if it is time to render the next frame.
+    pub fn is_frame_due(&self) -> bool {
+        match self.next_frame {
+            Some(next) => self.clock.now() >= next,
+            None => true,
+        }
+    }
+    /// Time at which the next frame should be rendered.
+    pub fn next_frame(&self) -> Instant {
+        self.next_frame.unwrap_or_else(|| self.clock.now())
+    }
+
+    /// Call once a frame has been rendered. This schedules the next frame and returns the window
+    /// state that waits until then. If rendering has fallen more than a frame behind, the schedule
+    /// restarts from the current time rather than rendering extra frames to catch up.
+    pub fn frame_end(&mut self) -> WindowState {
+        let now = self.clock.now();
+        let mut next = self.next_frame.unwrap_or(now) + self.frame_time;
+        if next <= now {
+            next = now + self.frame_time;
+        }
+        self.next_frame = Some(next);
+        WindowState::WaitUntil(next)
+    }
+    /// Window state that waits until the next frame without scheduling a new frame (ex. for events
+    /// that do not render).
+    pub fn wait(&self) -> WindowState {
+        WindowState::WaitUntil(self.next_frame())
+    }
+}
+
+fn frame_time(fps: f64) -> Duration {
+    assert!(
+        fps > 0.0 && fps.is_finite(),
+        "Frame rate must be a positive number of frames per second: {}",
+        fps
+    );
+    Duration::from_nanos((1_000_000_000.0 / fps) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_step::ManualClock;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+
+    #[test]
+    fn frames_keep_cadence() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let mut limiter = FrameLimiter::with_clock(100.0, clock.clone());
+        assert!(limiter.is_frame_due());
+        limiter.frame_end();
+        assert_eq!(limiter.next_frame(), start + ms(10));
+        assert!(!limiter.is_frame_due());
+
+        // Frames that end a little late do not push back the following frames
+        clock.advance(ms(12));
+        assert!(limiter.is_frame_due());
+        limiter.frame_end();
+        assert_eq!(limiter.next_frame(), start + ms(20));
+        clock.advance(ms(8));
+        assert!(limiter.is_frame_due());
+        limiter.frame_end();
+        assert_eq!(limiter.next_frame(), start + ms(30));
+    }
+
+    #[test]
+    fn falling_behind_restarts_schedule() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let mut limiter = FrameLimiter::with_clock(100.0, clock.clone());
+        limiter.frame_end();
+        // More than a frame behind: the next frame is a full frame from now instead of catching up
+        clock.advance(ms(35));
+        limiter.frame_end();
+        assert_eq!(limiter.next_frame(), start + ms(45));
+        assert!(!limiter.is_frame_due());
+    }
+
+    #[test]
+    fn set_fps_keeps_scheduled_frame() {
+        let clock = ManualClock::new();
+        let start = clock.now();
+        let mut limiter = FrameLimiter::with_clock(100.0, clock.clone());
+        limiter.frame_end();
+        limiter.set_fps(50.0);
+        assert_eq!(limiter.next_frame(), start + ms(10));
+        clock.advance(ms(10));
+        limiter.frame_end();
+        assert_eq!(limiter.next_frame(), start + ms(30));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::time_step::{Clock, SystemClock};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Rolling statistics for the time between rendered frames (ex. for an on-screen performance
+/// overlay). Unlike TimeStep, which controls the simulation, this only measures rendering.
+///
+/// # Examples
+///
+/// ```
+/// let mut stats = glu::FrameStats::new(120);
+/// loop {
+///     // Render window...
+///     stats.tick();
+///     let txt = format!("{:.0} fps ({:.1} ms p99)", stats.avg_fps(), stats.p99_ms());
+/// }
+/// ```
+pub struct FrameStats<C: Clock = SystemClock> {
+    clock: C,
+    frame_times: VecDeque<Duration>,
+    capacity: usize,
+    last_inst: Option<Instant>,
+}
+
+impl FrameStats {
+    /// Creates a frame statistics collector that keeps the given number of recent frame times.
+    pub fn new(capacity: usize) -> Self {
+        Self::with_clock(capacity, SystemClock)
+    }
+}
+
+impl<C: Clock> FrameStats<C> {
+    /// Creates a frame statistics collector that keeps the given number of recent frame times,
+    /// using the given clock to measure them (ex. a ManualClock in tests).
+    pub fn with_clock(capacity: usize, clock: C) -> Self {
+        assert!(
+            capacity > 0,
+            "Frame statistics capacity must be greater than zero"
+        );
+        Self {
+            clock,
+            frame_times: VecDeque::with_capacity(capacity),
+            capacity,
+            last_inst: None,
+        }
+    }
+
+    /// Records the end of a rendered frame. The first call only starts the timer.
+    pub fn tick(&mut self) {
+        let now = self.clock.now();
+        if let Some(last) = self.last_inst {
+            if self.frame_times.len() == self.capacity {
+                self.frame_times.pop_front();
+            }
+            self.frame_times.push_back(now.duration_since(last));
+        }
+        self.last_inst = Some(now);
+    }
+    /// Discards all recorded frame times (ex. after a loading screen).
+    pub fn reset(&mut self) {
+        self.frame_times.clear();
+        self.last_inst = None;
+    }
+
+    /// Number of frame times currently recorded (at most the capacity).
+    pub fn len(&self) -> usize {
+        self.frame_times.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.frame_times.is_empty()
+    }
+
+    /// Average frames per second over the recorded frames (zero if no frames have been recorded).
+    pub fn avg_fps(&self) -> f64 {
+        let avg = self.avg_ms();
+        if avg > 0.0 {
+            1000.0 / avg
+        } else {
+            0.0
+        }
+    }
+    /// Average frame time in milliseconds.
+    pub fn avg_ms(&self) -> f64 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        let total: Duration = self.frame_times.iter().sum();
+        to_ms(total) / self.frame_times.len() as f64
+    }
+    /// Shortest frame time in milliseconds.
+    pub fn min_ms(&self) -> f64 {
+        self.frame_times
+            .iter()
+            .min()
+            .map(|d| to_ms(*d))
+            .unwrap_or(0.0)
+    }
+    /// Longest frame time in milliseconds.
+    pub fn max_ms(&self) -> f64 {
+        self.frame_times
+            .iter()
+            .max()
+            .map(|d| to_ms(*d))
+            .unwrap_or(0.0)
+    }
+    /// 99th percentile frame time in milliseconds (only 1% of frames took longer than this).
+    pub fn p99_ms(&self) -> f64 {
+        self.percentile_ms(99.0)
+    }
+    /// Frame time in milliseconds at the given percentile (from 0.0 to 100.0), using the nearest
+    /// recorded frame time.
+    pub fn percentile_ms(&self, percentile: f64) -> f64 {
+        if self.frame_times.is_empty() {
+            return 0.0;
+        }
+        let mut sorted: Vec<Duration> = self.frame_times.iter().copied().collect();
+        sorted.sort_unstable();
+        let p = percentile.clamp(0.0, 100.0) / 100.0;
+        let idx = ((sorted.len() - 1) as f64 * p).round() as usize;
+        to_ms(sorted[idx])
+    }
+}
+
+impl Default for FrameStats {
+    fn default() -> Self {
+        Self::new(120)
+    }
+}
+
+fn to_ms(d: Duration) -> f64 {
+    d.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time_step::ManualClock;
+
+    /// Stats with the given frame times (in milliseconds) recorded.
+    fn stats_with(capacity: usize, frame_ms: &[u64]) -> FrameStats<ManualClock> {
+        let clock = ManualClock::new();
+        let mut stats = FrameStats::with_clock(capacity, clock.clone());
+        stats.tick();
+        for &ms in frame_ms {
+            clock.advance(Duration::from_millis(ms));
+            stats.tick();
+        }
+        stats
+    }
+
+    #[test]
+    fn oldest_frames_are_evicted() {
+        let stats = stats_with(3, &[1, 2, 3, 4]);
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats.min_ms(), 2.0);
+        assert_eq!(stats.max_ms(), 4.0);
+        assert_eq!(stats.avg_ms(), 3.0);
+        assert_eq!(stats.avg_fps(), 1000.0 / 3.0);
+    }
+
+    #[test]
+    fn percentile_uses_nearest_frame() {
+        let stats = stats_with(10, &[7, 1, 10, 3, 5, 2, 9, 4, 8, 6]);
+        assert_eq!(stats.percentile_ms(0.0), 1.0);
+        // Index 2.25 rounds down, 4.5 rounds up and 8.91 rounds up
+        assert_eq!(stats.percentile_ms(25.0), 3.0);
+        assert_eq!(stats.percentile_ms(50.0), 6.0);
+        assert_eq!(stats.p99_ms(), 10.0);
+        assert_eq!(stats.percentile_ms(150.0), 10.0);
+    }
+
+    #[test]
+    fn reset_restarts_timer() {
+        let clock = ManualClock::new();
+        let mut stats = FrameStats::with_clock(10, clock.clone());
+        stats.tick();
+        clock.advance(Duration::from_millis(5));
+        stats.tick();
+        stats.reset();
+        assert!(stats.is_empty());
+        assert_eq!(stats.p99_ms(), 0.0);
+        // The time spent before the first tick after a reset is not recorded
+        clock.advance(Duration::from_millis(500));
+        stats.tick();
+        clock.advance(Duration::from_millis(5));
+        stats.tick();
+        assert_eq!(stats.len(), 1);
+        assert_eq!(stats.max_ms(), 5.0);
+    }
+}
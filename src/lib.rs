@@ -5,27 +5,63 @@
 //! Main components:
 //!
 //! - AsciiText
+//! - TextBatch
+//! - ShapeRenderer
+//! - SpriteBatch
 //! - Event
 //! - EventState
+//! - TextInput
+//! - ActionMap
 //! - TimeStep
+//! - FrameLimiter
+//! - FrameStats
 //!
 
 #[macro_use]
 extern crate glium;
 
+mod action_map;
 mod ascii_text;
 mod ascii_text_img;
+mod cp437;
+mod display_ext;
+mod dynamic_mesh;
 mod event;
 mod event_state;
+mod frame_limiter;
+mod frame_stats;
+mod keys;
+mod monitor;
 mod screen_units;
+mod shape_renderer;
+mod sprite_batch;
+mod text_input;
 mod time_step;
 mod window;
 
-pub use crate::ascii_text::AsciiText;
-pub use crate::event::{AxisId, ButtonId, Event, FingerId, MouseButton, ScanCode, TouchPhase};
-pub use crate::event_state::{EventState, MouseButtonState};
-pub use crate::screen_units::Screen2d;
-pub use crate::time_step::TimeStep;
+pub use crate::action_map::{ActionMap, Binding};
+pub use crate::ascii_text::{AsciiText, TextAlign, TextBatch, TextStyle, TextVAlign};
+pub use crate::cp437::to_cp437;
+pub use crate::display_ext::{DisplayExt, FrameGuard};
+pub use crate::event::{
+    device_id_u64, window_id_u64, AxisId, ButtonId, Event, FingerId, Modifiers, MouseButton,
+    ScanCode, TouchPhase,
+};
+#[cfg(feature = "gilrs")]
+pub use crate::event::{GamepadAxis, GamepadButton, GamepadId};
+pub use crate::event_state::{EventState, InputSnapshot, MouseButtonState};
+pub use crate::frame_limiter::FrameLimiter;
+pub use crate::frame_stats::FrameStats;
+pub use crate::keys::{key_from_str, key_to_str};
+pub use crate::monitor::{MonitorInfo, VideoModeInfo};
+pub use crate::screen_units::{screen_ortho, screen_ortho_logical, Rect2d, Screen2d};
+pub use crate::shape_renderer::ShapeRenderer;
+pub use crate::sprite_batch::SpriteBatch;
+pub use crate::text_input::TextInput;
+pub use crate::time_step::{Clock, ManualClock, SystemClock, TimeStep};
 pub use crate::window::*;
 pub use glium::glutin::event::{DeviceId, VirtualKeyCode};
+pub use glium::glutin::event_loop::EventLoopProxy;
+pub use glium::glutin::monitor::{MonitorHandle, VideoMode};
 pub use glium::glutin::window::WindowId;
+pub use glium::glutin::{Api, GlProfile, GlRequest};
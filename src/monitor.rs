@@ -0,0 +1,61 @@
+use crate::window::FullscreenMode;
+use glium::glutin::monitor::{MonitorHandle, VideoMode};
+
+/// Summary of a monitor (ex. for a monitor picker in a settings screen). Use borderless or one of
+/// the video modes to make a window fullscreen on the monitor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    /// Size of the monitor in physical pixels.
+    pub physical_size: [u32; 2],
+    /// Position of the top-left corner of the monitor relative to the desktop, in physical pixels.
+    pub position: [i32; 2],
+    pub hidpi_factor: f32,
+    /// Video modes supported by the monitor in exclusive fullscreen.
+    pub video_modes: Vec<VideoModeInfo>,
+    handle: MonitorHandle,
+}
+impl MonitorInfo {
+    pub(crate) fn from_handle(handle: MonitorHandle) -> Self {
+        let size = handle.size();
+        let position = handle.position();
+        Self {
+            name: handle.name(),
+            physical_size: [size.width, size.height],
+            position: [position.x, position.y],
+            hidpi_factor: handle.scale_factor() as f32,
+            video_modes: handle.video_modes().map(VideoModeInfo::from_mode).collect(),
+            handle,
+        }
+    }
+    /// Fullscreen mode for a borderless window covering this monitor.
+    pub fn borderless(&self) -> FullscreenMode {
+        FullscreenMode::Borderless(Some(self.handle.clone()))
+    }
+}
+
+/// Resolution, bit depth, and refresh rate supported by a monitor.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VideoModeInfo {
+    /// Resolution in physical pixels.
+    pub physical_size: [u32; 2],
+    pub bit_depth: u16,
+    /// Refresh rate in Hz.
+    pub refresh_rate: u16,
+    mode: VideoMode,
+}
+impl VideoModeInfo {
+    fn from_mode(mode: VideoMode) -> Self {
+        let size = mode.size();
+        Self {
+            physical_size: [size.width, size.height],
+            bit_depth: mode.bit_depth(),
+            refresh_rate: mode.refresh_rate(),
+            mode,
+        }
+    }
+    /// Fullscreen mode that uses this video mode (this may change the monitor resolution).
+    pub fn exclusive(&self) -> FullscreenMode {
+        FullscreenMode::Exclusive(self.mode.clone())
+    }
+}
@@ -6,17 +6,35 @@ use noisy_float::prelude::*;
 ///
 /// Note: All math operations are performed in logical pixel units.
 #[derive(Clone, Copy, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(
+        crate = "serde_crate",
+        try_from = "Screen2dSerde",
+        into = "Screen2dSerde"
+    )
+)]
 pub struct Screen2d {
     logical: [R32; 2],
     hidpi_factor: R32,
 }
 impl Screen2d {
     pub fn from_logical(logical: [f32; 2], hidpi_factor: f32) -> Self {
+        check_hidpi(hidpi_factor);
         Self {
             logical: [r32(logical[0]), r32(logical[1])],
             hidpi_factor: r32(hidpi_factor),
         }
     }
+    /// Creates a zero value (ex. the origin or an empty size).
+    pub fn zero(hidpi_factor: f32) -> Self {
+        Self::from_logical([0.0, 0.0], hidpi_factor)
+    }
+    /// Creates a value with both logical components set to v.
+    pub fn splat(v: f32, hidpi_factor: f32) -> Self {
+        Self::from_logical([v, v], hidpi_factor)
+    }
     pub fn from_physical(physical: [i32; 2], hidpi_factor: f32) -> Self {
         Self::from_physical_f32([physical[0] as f32, physical[1] as f32], hidpi_factor)
     }
@@ -24,9 +42,16 @@ impl Screen2d {
         Self::from_physical_f32([physical[0] as f32, physical[1] as f32], hidpi_factor)
     }
     pub fn from_physical_f32(physical: [f32; 2], hidpi_factor: f32) -> Self {
+        check_hidpi(hidpi_factor);
         let logical = [physical[0] / hidpi_factor, physical[1] / hidpi_factor];
         Self::from_logical(logical, hidpi_factor)
     }
+    pub fn from_physical_f64(physical: [f64; 2], hidpi_factor: f32) -> Self {
+        check_hidpi(hidpi_factor);
+        let f = hidpi_factor as f64;
+        let logical = [(physical[0] / f) as f32, (physical[1] / f) as f32];
+        Self::from_logical(logical, hidpi_factor)
+    }
     pub(crate) fn from_logical_r32(logical: [R32; 2], hidpi_factor: R32) -> Self {
         Self {
             logical,
@@ -46,7 +71,7 @@ impl Screen2d {
         pos: &PhysicalPosition<f64>,
         hidpi_factor: R32,
     ) -> Self {
-        Self::from_physical_f32([pos.x as f32, pos.y as f32], hidpi_factor.raw())
+        Self::from_physical_f64([pos.x, pos.y], hidpi_factor.raw())
     }
     pub(crate) fn from_line_delta(
         delta_x: R32,
@@ -62,6 +87,34 @@ impl Screen2d {
     pub fn logical(&self) -> [f32; 2] {
         [self.logical[0].raw(), self.logical[1].raw()]
     }
+    /// The hidpi factor used when converting to physical units.
+    pub fn hidpi_factor(&self) -> f32 {
+        self.hidpi_factor.raw()
+    }
+    /// Changes the hidpi factor while keeping the same logical values. The physical values will
+    /// change (ex. a UI element keeps its logical size when moved to a monitor with a different
+    /// scale factor).
+    pub fn with_hidpi(self, new_factor: f32) -> Self {
+        check_hidpi(new_factor);
+        Self {
+            logical: self.logical,
+            hidpi_factor: r32(new_factor),
+        }
+    }
+    /// Changes the hidpi factor while keeping the same physical values. The logical values will be
+    /// recalculated (ex. a position in actual pixels that must not move).
+    pub fn rescaled_physical(self, new_factor: f32) -> Self {
+        check_hidpi(new_factor);
+        let f = self.hidpi_factor;
+        Self::from_physical_f32(
+            [(self.logical[0] * f).raw(), (self.logical[1] * f).raw()],
+            new_factor,
+        )
+    }
+    /// Logical values as a tuple.
+    pub fn as_tuple(&self) -> (f32, f32) {
+        (self.logical[0].raw(), self.logical[1].raw())
+    }
     pub fn physical(&self) -> [i32; 2] {
         [
             self.to_physical(self.logical[0]),
@@ -78,10 +131,143 @@ impl Screen2d {
         }
         [phys[0] as u32, phys[1] as u32]
     }
+    /// Indicates if both logical components are exactly zero.
+    pub fn is_zero(&self) -> bool {
+        self.logical[0] == 0.0 && self.logical[1] == 0.0
+    }
+    /// Indicates if each logical component is within epsilon of the other value's (ex. to check if
+    /// an animated position has settled). The hidpi factors are not compared.
+    pub fn approx_eq(&self, other: &Self, epsilon: f32) -> bool {
+        let [ax, ay] = self.logical();
+        let [bx, by] = other.logical();
+        (ax - bx).abs() <= epsilon && (ay - by).abs() <= epsilon
+    }
+    /// Snaps the value to the nearest whole physical pixel (ex. to keep UI elements sharp). The
+    /// logical values are adjusted to match, so they may be fractional.
+    pub fn round_to_physical(self) -> Self {
+        let [x, y] = self.physical();
+        Self::from_physical([x, y], self.hidpi_factor.raw())
+    }
+    /// Length of the value (in logical units).
+    pub fn length(&self) -> f32 {
+        self.length_squared().sqrt()
+    }
+    /// Squared length of the value (in logical units).
+    pub fn length_squared(&self) -> f32 {
+        let [x, y] = self.logical();
+        x * x + y * y
+    }
+    /// Distance between the two positions (in logical units).
+    pub fn distance(&self, other: &Self) -> f32 {
+        (*self - *other).length()
+    }
+    /// Dot product of the two values (in logical units).
+    pub fn dot(&self, other: &Self) -> f32 {
+        let [ax, ay] = self.logical();
+        let [bx, by] = other.logical();
+        ax * bx + ay * by
+    }
+    /// 2D cross product of the two values (in logical units). This is the z-component of the 3D
+    /// cross product, which is positive when other is clockwise from self in screen coordinates
+    /// (where the y-axis points down).
+    pub fn cross(&self, other: &Self) -> f32 {
+        let [ax, ay] = self.logical();
+        let [bx, by] = other.logical();
+        ax * by - ay * bx
+    }
+    /// Component-wise minimum of the two values (in logical units). The result uses the hidpi
+    /// factor of self (the hidpi factor of other is ignored).
+    pub fn min(self, other: Self) -> Self {
+        let a = self.logical;
+        let b = other.logical;
+        Self {
+            logical: [a[0].min(b[0]), a[1].min(b[1])],
+            hidpi_factor: self.hidpi_factor,
+        }
+    }
+    /// Component-wise maximum of the two values (in logical units). The result uses the hidpi
+    /// factor of self (the hidpi factor of other is ignored).
+    pub fn max(self, other: Self) -> Self {
+        let a = self.logical;
+        let b = other.logical;
+        Self {
+            logical: [a[0].max(b[0]), a[1].max(b[1])],
+            hidpi_factor: self.hidpi_factor,
+        }
+    }
+    /// Restricts each component to the range lo..=hi (in logical units). The result uses the hidpi
+    /// factor of self.
+    pub fn clamp(self, lo: Self, hi: Self) -> Self {
+        self.max(lo).min(hi)
+    }
+    /// Linear interpolation between self (t=0.0) and other (t=1.0) in logical units. Values of t
+    /// outside of the 0.0 to 1.0 range will extrapolate. The result uses the hidpi factor of self.
+    pub fn lerp(self, other: Self, t: f32) -> Self {
+        let a = self.logical;
+        let b = other.logical;
+        let t = r32(t);
+        Self {
+            logical: [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t],
+            hidpi_factor: self.hidpi_factor,
+        }
+    }
     fn to_physical(&self, v: R32) -> i32 {
         (v * self.hidpi_factor).raw().round() as i32
     }
 }
+/// Panics with a clear message for a hidpi factor that would produce invalid logical values (ex.
+/// dividing by zero when converting from physical units).
+pub(crate) fn check_hidpi(hidpi_factor: f32) {
+    assert!(
+        hidpi_factor > 0.0 && hidpi_factor.is_finite(),
+        "HiDPI factor must be a positive number: {}",
+        hidpi_factor
+    );
+}
+
+/// Serialized form of Screen2d (the raw logical values and hidpi factor).
+#[cfg(feature = "serde")]
+#[derive(serde_crate::Serialize, serde_crate::Deserialize)]
+#[serde(crate = "serde_crate")]
+struct Screen2dSerde {
+    logical: [f32; 2],
+    hidpi_factor: f32,
+}
+#[cfg(feature = "serde")]
+impl From<Screen2d> for Screen2dSerde {
+    fn from(s: Screen2d) -> Self {
+        Self {
+            logical: s.logical(),
+            hidpi_factor: s.hidpi_factor.raw(),
+        }
+    }
+}
+/// Deserializing rejects an invalid hidpi factor instead of panicking like `from_logical` does.
+#[cfg(feature = "serde")]
+impl std::convert::TryFrom<Screen2dSerde> for Screen2d {
+    type Error = String;
+    fn try_from(s: Screen2dSerde) -> Result<Self, String> {
+        if !(s.hidpi_factor > 0.0 && s.hidpi_factor.is_finite()) {
+            return Err(format!(
+                "HiDPI factor must be a positive number: {}",
+                s.hidpi_factor
+            ));
+        }
+        if s.logical[0].is_nan() || s.logical[1].is_nan() {
+            return Err(format!(
+                "Screen value must be a number: {},{}",
+                s.logical[0], s.logical[1]
+            ));
+        }
+        Ok(Screen2d::from_logical(s.logical, s.hidpi_factor))
+    }
+}
+impl From<Screen2d> for [f32; 2] {
+    /// Converts to logical units.
+    fn from(s: Screen2d) -> Self {
+        s.logical()
+    }
+}
 impl std::fmt::Display for Screen2d {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         let phys = self.physical();
@@ -133,3 +319,243 @@ impl std::ops::AddAssign for Screen2d {
         self.logical[1] += other.logical[1];
     }
 }
+impl std::ops::Mul<f32> for Screen2d {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        let a = self.logical;
+        let f = r32(factor);
+        Self {
+            logical: [a[0] * f, a[1] * f],
+            hidpi_factor: self.hidpi_factor,
+        }
+    }
+}
+impl std::ops::MulAssign<f32> for Screen2d {
+    fn mul_assign(&mut self, factor: f32) {
+        *self = *self * factor;
+    }
+}
+impl std::ops::Div<f32> for Screen2d {
+    type Output = Self;
+
+    fn div(self, divisor: f32) -> Self {
+        if divisor == 0.0 {
+            panic!("Cannot divide screen value by zero: {}", self);
+        }
+        let a = self.logical;
+        let d = r32(divisor);
+        Self {
+            logical: [a[0] / d, a[1] / d],
+            hidpi_factor: self.hidpi_factor,
+        }
+    }
+}
+impl std::ops::DivAssign<f32> for Screen2d {
+    fn div_assign(&mut self, divisor: f32) {
+        *self = *self / divisor;
+    }
+}
+
+/// Rectangular screen region made up of a top-left origin and a size. All operations are
+/// performed in logical pixel units, so this can be tested against EventState::mouse_pos
+/// directly.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct Rect2d {
+    pub origin: Screen2d,
+    pub size: Screen2d,
+}
+impl Rect2d {
+    pub fn new(origin: Screen2d, size: Screen2d) -> Self {
+        Self { origin, size }
+    }
+    pub fn from_logical(origin: [f32; 2], size: [f32; 2], hidpi_factor: f32) -> Self {
+        Self::new(
+            Screen2d::from_logical(origin, hidpi_factor),
+            Screen2d::from_logical(size, hidpi_factor),
+        )
+    }
+    pub fn from_physical(origin: [i32; 2], size: [u32; 2], hidpi_factor: f32) -> Self {
+        Self::new(
+            Screen2d::from_physical(origin, hidpi_factor),
+            Screen2d::from_physical_u32(size, hidpi_factor),
+        )
+    }
+    /// Creates the rectangle spanning the two corners (in any order).
+    pub fn from_corners(a: Screen2d, b: Screen2d) -> Self {
+        let min = a.min(b);
+        Self::new(min, a.max(b) - min)
+    }
+
+    /// Bottom-right corner of the rectangle.
+    pub fn end(&self) -> Screen2d {
+        self.origin + self.size
+    }
+    pub fn center(&self) -> Screen2d {
+        self.origin + self.size * 0.5
+    }
+    /// Indicates if the point is inside the rectangle. The top and left edges are included, while
+    /// the bottom and right edges are not (so adjacent rectangles do not overlap).
+    pub fn contains(&self, p: Screen2d) -> bool {
+        let [x, y] = p.logical();
+        let [x0, y0] = self.origin.logical();
+        let [x1, y1] = self.end().logical();
+        x >= x0 && x < x1 && y >= y0 && y < y1
+    }
+    /// Indicates if the two rectangles overlap (rectangles that only share an edge do not).
+    pub fn intersects(&self, other: &Rect2d) -> bool {
+        let [ax0, ay0] = self.origin.logical();
+        let [ax1, ay1] = self.end().logical();
+        let [bx0, by0] = other.origin.logical();
+        let [bx1, by1] = other.end().logical();
+        ax0 < bx1 && bx0 < ax1 && ay0 < by1 && by0 < ay1
+    }
+}
+
+/// Transform from screen coordinates (with the origin in the upper-left corner) to OpenGL
+/// coordinates for a window of the given size. This is the transform used by AsciiText,
+/// ShapeRenderer and SpriteBatch (with the logical size of the window), so custom geometry drawn
+/// with it lines up with the text.
+///
+/// The matrix is in row-major order, so it is applied in the vertex shader as
+/// `vec4(position, 0.0, 1.0) * matrix`.
+pub fn screen_ortho(width: f32, height: f32) -> [[f32; 4]; 4] {
+    // Scale and translate values
+    let xs: f32 = 2.0 / width;
+    let xt: f32 = -width / 2.0;
+    let ys: f32 = -2.0 / height;
+    let yt: f32 = -height / 2.0;
+
+    [
+        [xs, 0.0, 0.0, xt * xs],
+        [0.0, ys, 0.0, yt * ys],
+        [0.0, 0.0, 1.0, 0.0],
+        [0.0, 0.0, 0.0, 1.0],
+    ]
+}
+/// Same as screen_ortho, using the logical size (ex. EventState::win_dim or
+/// DisplayExt::framebuffer_size_screen).
+pub fn screen_ortho_logical(size: Screen2d) -> [[f32; 4]; 4] {
+    let [w, h] = size.logical();
+    screen_ortho(w, h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scalar_mul_and_div() {
+        let a = Screen2d::from_logical([2.0, 4.0], 2.0);
+        assert_eq!(a * 0.5, Screen2d::from_logical([1.0, 2.0], 2.0));
+        assert_eq!(a / 2.0, Screen2d::from_logical([1.0, 2.0], 2.0));
+        assert_eq!((a * 0.5).hidpi_factor(), 2.0);
+
+        let mut b = a;
+        b *= 3.0;
+        assert_eq!(b, Screen2d::from_logical([6.0, 12.0], 2.0));
+        b /= 6.0;
+        assert_eq!(b, Screen2d::from_logical([1.0, 2.0], 2.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cannot divide screen value by zero")]
+    fn div_by_zero_panics() {
+        let _ = Screen2d::from_logical([2.0, 4.0], 2.0) / 0.0;
+    }
+
+    #[test]
+    fn lerp() {
+        let a = Screen2d::from_logical([0.0, 10.0], 2.0);
+        let b = Screen2d::from_logical([10.0, 30.0], 1.0);
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 0.5), Screen2d::from_logical([5.0, 20.0], 2.0));
+        assert_eq!(a.lerp(b, 1.0), b.with_hidpi(2.0));
+        assert_eq!(a.lerp(b, 2.0), Screen2d::from_logical([20.0, 50.0], 2.0));
+    }
+
+    #[test]
+    fn dot_and_cross() {
+        let right = Screen2d::from_logical([2.0, 0.0], 1.0);
+        let down = Screen2d::from_logical([0.0, 3.0], 1.0);
+        let left = Screen2d::from_logical([-4.0, 0.0], 1.0);
+
+        // Perpendicular
+        assert_eq!(right.dot(&down), 0.0);
+        assert_eq!(right.cross(&down), 6.0);
+        assert_eq!(down.cross(&right), -6.0);
+
+        // Parallel
+        assert_eq!(right.dot(&right), 4.0);
+        assert_eq!(right.dot(&left), -8.0);
+        assert_eq!(right.cross(&left), 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "HiDPI factor must be a positive number: 0")]
+    fn zero_hidpi_factor_panics() {
+        let _ = Screen2d::from_physical([100, 100], 0.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "HiDPI factor must be a positive number")]
+    fn invalid_hidpi_factor_panics_on_rescale() {
+        let _ = Screen2d::from_logical([1.0, 2.0], 1.0).rescaled_physical(f32::NAN);
+    }
+
+    #[test]
+    fn rect_contains_is_half_open() {
+        let r = Rect2d::from_logical([10.0, 20.0], [30.0, 40.0], 1.0);
+        let p = |x, y| Screen2d::from_logical([x, y], 1.0);
+        assert!(r.contains(p(10.0, 20.0)));
+        assert!(r.contains(p(39.9, 59.9)));
+        assert!(!r.contains(p(40.0, 30.0)));
+        assert!(!r.contains(p(20.0, 60.0)));
+        assert!(!r.contains(p(9.9, 30.0)));
+        assert_eq!(r.center(), p(25.0, 40.0));
+        assert_eq!(Rect2d::from_corners(r.end(), r.origin), r);
+    }
+
+    #[test]
+    fn rect_intersects() {
+        let a = Rect2d::from_logical([0.0, 0.0], [10.0, 10.0], 1.0);
+        let overlapping = Rect2d::from_logical([5.0, 5.0], [10.0, 10.0], 1.0);
+        let adjacent = Rect2d::from_logical([10.0, 0.0], [10.0, 10.0], 1.0);
+        let inside = Rect2d::from_logical([2.0, 2.0], [1.0, 1.0], 1.0);
+        assert!(a.intersects(&overlapping));
+        assert!(overlapping.intersects(&a));
+        assert!(!a.intersects(&adjacent));
+        assert!(a.intersects(&inside));
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_round_trip() {
+        let a = Screen2d::from_logical([12.5, -3.0], 1.5);
+        let json = serde_json::to_string(&a).unwrap();
+        assert_eq!(json, r#"{"logical":[12.5,-3.0],"hidpi_factor":1.5}"#);
+        let b: Screen2d = serde_json::from_str(&json).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(b.hidpi_factor(), 1.5);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn serde_rejects_invalid_hidpi() {
+        let zero = serde_json::from_str::<Screen2d>(r#"{"logical":[1.0,2.0],"hidpi_factor":0.0}"#);
+        let err = zero.unwrap_err().to_string();
+        assert!(
+            err.contains("HiDPI factor must be a positive number"),
+            "{}",
+            err
+        );
+        let negative =
+            serde_json::from_str::<Screen2d>(r#"{"logical":[1.0,2.0],"hidpi_factor":-1.0}"#);
+        assert!(negative.is_err());
+    }
+}
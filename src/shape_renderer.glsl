@@ -0,0 +1,28 @@
+#version 140
+#define TEMPLATE_PROGRAM
+
+uniform mat4 matrix;
+
+#ifdef VERTEX_PROGRAM
+
+in vec2 position;
+in vec4 color;
+
+smooth out vec4 v_color;
+
+void main() {
+  v_color = color;
+  gl_Position = vec4(position, 0.0, 1.0) * matrix;
+}
+
+#else
+
+smooth in vec4 v_color;
+
+out vec4 f_color;
+
+void main() {
+  f_color = v_color;
+}
+
+#endif
@@ -0,0 +1,168 @@
+use crate::ascii_text::logical_transform;
+use crate::dynamic_mesh::DynamicMesh;
+use crate::screen_units::check_hidpi;
+
+#[derive(Clone, Copy, Debug)]
+struct ShapeVertex {
+    position: [f32; 2],
+    color: [f32; 4],
+}
+implement_vertex!(ShapeVertex, position, color);
+
+/// Filled and outlined shape writer, using the same coordinates as AsciiText (logical units from
+/// the upper-left corner of the window). This is useful for debug overlays and simple HUDs.
+///
+/// Shapes are collected until flush is called, and then drawn in the order they were added using a
+/// single draw call, so one renderer can draw any number of rectangles and lines each frame.
+///
+/// # Examples
+///
+/// ```
+/// let mut shapes = ShapeRenderer::new(&display);
+/// loop {
+///   let target = display.draw();
+///
+///   shapes.draw_rect([10.0, 10.0], [200.0, 20.0], [0.0, 0.0, 0.0, 0.5]);
+///   shapes.draw_rect_outline([10.0, 10.0], [200.0, 20.0], 1.0, [1.0, 1.0, 1.0, 1.0]);
+///   shapes.draw_line([10.0, 40.0], [210.0, 80.0], 2.0, [1.0, 0.0, 0.0, 1.0]);
+///   shapes.flush(&display, &mut target);
+///
+///   target.finish().unwrap();
+/// }
+/// ```
+pub struct ShapeRenderer {
+    program: glium::Program,
+    mesh: DynamicMesh<ShapeVertex>,
+    hidpi_override: Option<f32>,
+}
+impl ShapeRenderer {
+    /// Creates and initializes the shape shaders.
+    pub fn new(display: &glium::Display) -> Self {
+        let program_src = include_str!("shape_renderer.glsl").to_string();
+        let program = glium::Program::from_source(
+            display,
+            &program_src.replace("TEMPLATE_PROGRAM", "VERTEX_PROGRAM"),
+            &program_src.replace("TEMPLATE_PROGRAM", "FRAGMENT_PROGRAM"),
+            None,
+        )
+        .expect("Failed to compile shape shader: shape_renderer.glsl");
+
+        Self {
+            program,
+            mesh: DynamicMesh::new("shape"),
+            hidpi_override: None,
+        }
+    }
+
+    /// Uses the given hidpi factor for the shape coordinates instead of the window's factor (ex. the
+    /// same factor as EventState::set_forced_hidpi, so shapes line up with mouse positions). Use
+    /// None to go back to the window's factor.
+    pub fn set_hidpi_override(&mut self, factor: Option<f32>) {
+        if let Some(f) = factor {
+            check_hidpi(f);
+        }
+        self.hidpi_override = factor;
+    }
+
+    /// Adds a filled rectangle. The position is the upper-left corner, and the color is in RGBA
+    /// format (alpha blending is supported).
+    pub fn draw_rect(&mut self, pos: [f32; 2], size: [f32; 2], color: [f32; 4]) {
+        let [x, y] = pos;
+        let [w, h] = size;
+        self.add_quad([[x, y], [x + w, y], [x + w, y + h], [x, y + h]], color);
+    }
+
+    /// Adds the outline of a rectangle. The outline is drawn inside of the rectangle, so the
+    /// outer edge matches a filled rectangle with the same position and size.
+    pub fn draw_rect_outline(
+        &mut self,
+        pos: [f32; 2],
+        size: [f32; 2],
+        thickness: f32,
+        color: [f32; 4],
+    ) {
+        let [x, y] = pos;
+        let [w, h] = size;
+        let t = thickness.min(w / 2.0).min(h / 2.0);
+        self.draw_rect([x, y], [w, t], color);
+        self.draw_rect([x, y + h - t], [w, t], color);
+        self.draw_rect([x, y + t], [t, h - 2.0 * t], color);
+        self.draw_rect([x + w - t, y + t], [t, h - 2.0 * t], color);
+    }
+
+    /// Adds a line between the two points. The thickness is in logical units.
+    pub fn draw_line(&mut self, from: [f32; 2], to: [f32; 2], thickness: f32, color: [f32; 4]) {
+        let dx = to[0] - from[0];
+        let dy = to[1] - from[1];
+        let len = (dx * dx + dy * dy).sqrt();
+        if len == 0.0 {
+            return;
+        }
+        // Offset perpendicular to the line by half of the thickness on each side
+        let nx = -dy / len * thickness / 2.0;
+        let ny = dx / len * thickness / 2.0;
+        self.add_quad(
+            [
+                [from[0] + nx, from[1] + ny],
+                [to[0] + nx, to[1] + ny],
+                [to[0] - nx, to[1] - ny],
+                [from[0] - nx, from[1] - ny],
+            ],
+            color,
+        );
+    }
+
+    /// Indicates if there are no shapes waiting to be drawn.
+    pub fn is_empty(&self) -> bool {
+        self.mesh.is_empty()
+    }
+
+    /// Removes all shapes without drawing them.
+    pub fn clear(&mut self) {
+        self.mesh.clear();
+    }
+
+    /// Draws all of the shapes using a single draw call, then clears them.
+    pub fn flush<DrawSurface: glium::Surface>(
+        &mut self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        let (vertex_buffer, indices) = self.mesh.upload(display);
+        let uniforms = uniform! {
+            matrix: logical_transform(display, self.hidpi_override),
+        };
+
+        let draw_params = glium::draw_parameters::DrawParameters {
+            blend: glium::Blend::alpha_blending(),
+            ..Default::default()
+        };
+
+        target
+            .draw(
+                vertex_buffer,
+                indices,
+                &self.program,
+                &uniforms,
+                &draw_params,
+            )
+            .expect("Failed to render shapes");
+
+        self.clear();
+    }
+
+    /// Adds a quad with the corners in order around the edge.
+    fn add_quad(&mut self, corners: [[f32; 2]; 4], color: [f32; 4]) {
+        let vertex = |position: [f32; 2]| ShapeVertex { position, color };
+        self.mesh.push_quad([
+            vertex(corners[0]),
+            vertex(corners[1]),
+            vertex(corners[2]),
+            vertex(corners[3]),
+        ]);
+    }
+}
@@ -0,0 +1,188 @@
+use crate::ascii_text::logical_transform;
+use crate::dynamic_mesh::DynamicMesh;
+use crate::screen_units::check_hidpi;
+
+#[derive(Clone, Copy, Debug)]
+struct SpriteVertex {
+    position: [f32; 2],
+    color: [f32; 4],
+    texcoord: [f32; 2],
+}
+implement_vertex!(SpriteVertex, position, color, texcoord);
+
+/// Texture sprite writer, using the same coordinates as AsciiText (logical units from the
+/// upper-left corner of the window). This is useful for icons, cursors, and other 2D images. Each
+/// batch draws from a single texture, which can be a sprite sheet (see draw_region).
+///
+/// Sprites are collected until flush is called, and then drawn using a single draw call. Since a
+/// batch only has one texture, use a sprite sheet (or one batch per texture) for different images.
+///
+/// # Examples
+///
+/// ```
+/// let mut icons = SpriteBatch::new(&display, icon_texture);
+/// loop {
+///   let target = display.draw();
+///
+///   // Draw the 16x16 icon in the upper-left corner of the sheet at twice the size
+///   icons.draw_region([0.0, 0.0, 16.0, 16.0], [10.0, 10.0, 32.0, 32.0], [1.0, 1.0, 1.0, 1.0]);
+///   icons.flush(&display, &mut target);
+///
+///   target.finish().unwrap();
+/// }
+/// ```
+pub struct SpriteBatch {
+    texture: glium::texture::Texture2d,
+    program: glium::Program,
+    smooth: bool,
+    mesh: DynamicMesh<SpriteVertex>,
+    hidpi_override: Option<f32>,
+}
+impl SpriteBatch {
+    /// Creates and initializes the sprite shaders for drawing from the given texture. The first row
+    /// of the texture data is the top of the image (this is the case for from_rgba, but not for
+    /// RawImage2d::from_raw_rgba_reversed).
+    pub fn new(display: &glium::Display, texture: glium::texture::Texture2d) -> Self {
+        let program_src = include_str!("sprite_batch.glsl").to_string();
+        let program = glium::Program::from_source(
+            display,
+            &program_src.replace("TEMPLATE_PROGRAM", "VERTEX_PROGRAM"),
+            &program_src.replace("TEMPLATE_PROGRAM", "FRAGMENT_PROGRAM"),
+            None,
+        )
+        .expect("Failed to compile sprite shader: sprite_batch.glsl");
+
+        Self {
+            texture,
+            program,
+            smooth: true,
+            mesh: DynamicMesh::new("sprite"),
+            hidpi_override: None,
+        }
+    }
+
+    /// Creates the sprite batch using image data in RGBA format, with the top row first.
+    pub fn from_rgba(display: &glium::Display, rgba: Vec<u8>, width: u32, height: u32) -> Self {
+        let raw = glium::texture::RawImage2d::from_raw_rgba(rgba, (width, height));
+        let texture =
+            glium::texture::Texture2d::new(display, raw).expect("Failed to load sprite texture");
+        Self::new(display, texture)
+    }
+
+    pub fn texture(&self) -> &glium::texture::Texture2d {
+        &self.texture
+    }
+
+    /// Sets if scaled sprites are smoothed (linear filtering) or keep sharp pixel edges (nearest
+    /// filtering, ex. for pixel art). The default is true.
+    pub fn set_smooth(&mut self, smooth: bool) {
+        self.smooth = smooth;
+    }
+
+    /// Uses the given hidpi factor for the destination rectangles instead of the window's factor
+    /// (ex. when EventState::set_forced_hidpi is used for a custom UI scale). Use None to go back
+    /// to the window's factor.
+    pub fn set_hidpi_override(&mut self, factor: Option<f32>) {
+        if let Some(f) = factor {
+            check_hidpi(f);
+        }
+        self.hidpi_override = factor;
+    }
+
+    /// Adds the entire texture stretched over the destination rectangle, given as
+    /// [x, y, width, height] in logical units.
+    pub fn draw(&mut self, dst: [f32; 4]) {
+        self.draw_tinted(dst, [1.0, 1.0, 1.0, 1.0]);
+    }
+
+    /// Adds the entire texture with the color multiplied by the tint (RGBA format, so the alpha
+    /// value can be used to fade the sprite).
+    pub fn draw_tinted(&mut self, dst: [f32; 4], tint: [f32; 4]) {
+        let (w, h) = self.texture.dimensions();
+        self.draw_region([0.0, 0.0, w as f32, h as f32], dst, tint);
+    }
+
+    /// Adds a region of the texture (ex. one sprite in a sprite sheet). The source rectangle is
+    /// [x, y, width, height] in texture pixels, with the origin in the upper-left corner of the
+    /// image. The destination rectangle is [x, y, width, height] in logical units.
+    pub fn draw_region(&mut self, src: [f32; 4], dst: [f32; 4], tint: [f32; 4]) {
+        let (tw, th) = self.texture.dimensions();
+        let (tw, th) = (tw as f32, th as f32);
+
+        let u0 = src[0] / tw;
+        let u1 = (src[0] + src[2]) / tw;
+        let v0 = src[1] / th;
+        let v1 = (src[1] + src[3]) / th;
+
+        let [x, y, w, h] = dst;
+        let vertex = |position: [f32; 2], texcoord: [f32; 2]| SpriteVertex {
+            position,
+            color: tint,
+            texcoord,
+        };
+        self.mesh.push_quad([
+            vertex([x, y], [u0, v0]),
+            vertex([x + w, y], [u1, v0]),
+            vertex([x + w, y + h], [u1, v1]),
+            vertex([x, y + h], [u0, v1]),
+        ]);
+    }
+
+    /// Indicates if there are no sprites waiting to be drawn.
+    pub fn is_empty(&self) -> bool {
+        self.mesh.is_empty()
+    }
+
+    /// Removes all sprites without drawing them.
+    pub fn clear(&mut self) {
+        self.mesh.clear();
+    }
+
+    /// Draws all of the sprites using a single draw call, then clears them. Sprites are drawn in
+    /// the order they were added.
+    pub fn flush<DrawSurface: glium::Surface>(
+        &mut self,
+        display: &glium::Display,
+        target: &mut DrawSurface,
+    ) {
+        if self.is_empty() {
+            return;
+        }
+
+        let (magnify, minify) = if self.smooth {
+            (
+                glium::uniforms::MagnifySamplerFilter::Linear,
+                glium::uniforms::MinifySamplerFilter::Linear,
+            )
+        } else {
+            (
+                glium::uniforms::MagnifySamplerFilter::Nearest,
+                glium::uniforms::MinifySamplerFilter::Nearest,
+            )
+        };
+        let (vertex_buffer, indices) = self.mesh.upload(display);
+        let uniforms = uniform! {
+            matrix: logical_transform(display, self.hidpi_override),
+            sprite_texture: glium::uniforms::Sampler::new(&self.texture)
+                .magnify_filter(magnify)
+                .minify_filter(minify),
+        };
+
+        let draw_params = glium::draw_parameters::DrawParameters {
+            blend: glium::Blend::alpha_blending(),
+            ..Default::default()
+        };
+
+        target
+            .draw(
+                vertex_buffer,
+                indices,
+                &self.program,
+                &uniforms,
+                &draw_params,
+            )
+            .expect("Failed to render sprites");
+
+        self.clear();
+    }
+}
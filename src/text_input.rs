@@ -0,0 +1,181 @@
+use crate::event::Event;
+use glium::glutin::event::VirtualKeyCode;
+
+/// Editable text built from KeyText and KeyDown events (ex. for a text field). Typed characters are
+/// inserted at the cursor, and the backspace, delete, left, right, home and end keys are handled.
+///
+/// # Examples
+///
+/// ```
+/// let mut name = glu::TextInput::new();
+/// win.run(move |display, e, event_state| {
+///     if name.handle(&e) {
+///         println!("Name: {}", name.text());
+///     }
+///     glu::WindowState::Run
+/// });
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct TextInput {
+    text: String,
+    cursor: usize,
+    multiline: bool,
+}
+
+impl TextInput {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    pub fn with_text(text: &str) -> Self {
+        let mut input = Self::new();
+        input.set_text(text);
+        input
+    }
+    /// Sets if new lines can be entered using the enter key (the default is false).
+    pub fn set_multiline(&mut self, multiline: bool) {
+        self.multiline = multiline;
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+    /// Replaces the text and moves the cursor to the end.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.cursor = self.text.len();
+    }
+    pub fn clear(&mut self) {
+        self.text.clear();
+        self.cursor = 0;
+    }
+    /// Position of the cursor as a byte offset into the text (this is always on a character
+    /// boundary).
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+    /// Position of the cursor as the number of characters before it.
+    pub fn cursor_chars(&self) -> usize {
+        self.text[..self.cursor].chars().count()
+    }
+    /// Moves the cursor to the given byte offset. Panics if the offset is not on a character
+    /// boundary.
+    pub fn set_cursor(&mut self, cursor: usize) {
+        assert!(
+            self.text.is_char_boundary(cursor),
+            "Cursor must be on a character boundary"
+        );
+        self.cursor = cursor;
+    }
+    /// Inserts the text at the cursor, and moves the cursor to the end of the inserted text.
+    pub fn insert_str(&mut self, text: &str) {
+        self.text.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Updates the text based on the event. Returns true if the text or cursor changed.
+    pub fn handle<T>(&mut self, event: &Event<T>) -> bool {
+        match *event {
+            Event::KeyText { ch: Some(ch), .. } => {
+                if ch == '\n' && !self.multiline {
+                    return false;
+                }
+                self.text.insert(self.cursor, ch);
+                self.cursor += ch.len_utf8();
+                true
+            }
+            Event::ImeCommit { ref text, .. } if !text.is_empty() => {
+                self.insert_str(text);
+                true
+            }
+            Event::KeyDown {
+                vkey: Some(vkey), ..
+            } => self.handle_key(vkey),
+            _ => false,
+        }
+    }
+
+    fn handle_key(&mut self, vkey: VirtualKeyCode) -> bool {
+        match vkey {
+            VirtualKeyCode::Back => match self.prev_boundary() {
+                Some(prev) => {
+                    self.text.replace_range(prev..self.cursor, "");
+                    self.cursor = prev;
+                    true
+                }
+                None => false,
+            },
+            VirtualKeyCode::Delete => match self.next_boundary() {
+                Some(next) => {
+                    self.text.replace_range(self.cursor..next, "");
+                    true
+                }
+                None => false,
+            },
+            VirtualKeyCode::Left => self.move_cursor(self.prev_boundary()),
+            VirtualKeyCode::Right => self.move_cursor(self.next_boundary()),
+            VirtualKeyCode::Home => self.move_cursor(Some(0)),
+            VirtualKeyCode::End => self.move_cursor(Some(self.text.len())),
+            _ => false,
+        }
+    }
+    fn move_cursor(&mut self, pos: Option<usize>) -> bool {
+        match pos {
+            Some(pos) if pos != self.cursor => {
+                self.cursor = pos;
+                true
+            }
+            _ => false,
+        }
+    }
+    fn prev_boundary(&self) -> Option<usize> {
+        self.text[..self.cursor]
+            .char_indices()
+            .last()
+            .map(|(i, _)| i)
+    }
+    fn next_boundary(&self) -> Option<usize> {
+        self.text[self.cursor..]
+            .chars()
+            .next()
+            .map(|ch| self.cursor + ch.len_utf8())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits_at_cursor() {
+        let mut input = TextInput::with_text("helo");
+        assert!(input.handle_key(VirtualKeyCode::Left));
+        input.insert_str("l");
+        assert_eq!(input.text(), "hello");
+        assert_eq!(input.cursor(), 4);
+
+        assert!(input.handle_key(VirtualKeyCode::Home));
+        assert!(!input.handle_key(VirtualKeyCode::Back));
+        assert!(input.handle_key(VirtualKeyCode::Delete));
+        assert_eq!(input.text(), "ello");
+
+        assert!(input.handle_key(VirtualKeyCode::End));
+        assert!(!input.handle_key(VirtualKeyCode::Right));
+        assert!(input.handle_key(VirtualKeyCode::Back));
+        assert_eq!(input.text(), "ell");
+    }
+
+    #[test]
+    fn edits_multi_byte_characters() {
+        let mut input = TextInput::with_text("aé日");
+        assert_eq!(input.cursor(), 6);
+        assert_eq!(input.cursor_chars(), 3);
+
+        assert!(input.handle_key(VirtualKeyCode::Left));
+        assert_eq!(input.cursor(), 3);
+        assert!(input.handle_key(VirtualKeyCode::Back));
+        assert_eq!(input.text(), "a日");
+        assert_eq!(input.cursor(), 1);
+        assert!(input.handle_key(VirtualKeyCode::Delete));
+        assert_eq!(input.text(), "a");
+    }
+}
@@ -1,4 +1,6 @@
-use std::time::Instant;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 /// Represents a simulation time step. This class allows for discrete, frame-rate-independent
 /// actions to be performed within the OpenGL render loop.
@@ -13,24 +15,81 @@ use std::time::Instant;
 ///   // Render window...
 /// }
 /// ```
-pub struct TimeStep {
+pub struct TimeStep<C: Clock = SystemClock> {
+    clock: C,
     freq_nanos: u64,
     max_missed: u32,
     last_inst: Instant,
     elapsed_nanos: u64,
+    paused: bool,
+    max_frame_nanos: Option<u64>,
+    total_steps: u64,
+    total_elapsed_nanos: u64,
+    dropped_steps: u64,
+    delta_inst: Instant,
 }
 
 impl TimeStep {
     /// Creates a new time step running at the specified frequency (in milliseconds).
     pub fn for_freq_ms(freq_msec: u32) -> Self {
+        Self::for_freq(Duration::from_millis(freq_msec as u64))
+    }
+    /// Creates a new time step running at the specified frequency. Unlike for_freq_ms, this
+    /// supports sub-millisecond precision.
+    pub fn for_freq(freq: Duration) -> Self {
+        Self::with_clock(freq, SystemClock)
+    }
+    /// Creates a new time step that runs the given number of times per second (ex. 144.0).
+    pub fn for_hz(hz: f64) -> Self {
+        assert!(
+            hz > 0.0 && hz.is_finite(),
+            "Time step rate must be a positive number of steps per second: {}",
+            hz
+        );
+        Self::for_freq(Duration::from_nanos((1_000_000_000.0 / hz) as u64))
+    }
+}
+
+impl<C: Clock> TimeStep<C> {
+    /// Creates a new time step running at the specified frequency, using the given clock to
+    /// measure elapsed time (ex. a ManualClock to control the time step in tests).
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// let clock = glu::ManualClock::new();
+    /// let mut step = glu::TimeStep::with_clock(Duration::from_millis(10), clock.clone());
+    /// clock.advance(Duration::from_millis(25));
+    /// assert_eq!(step.tick(|| {}), 2);
+    /// ```
+    pub fn with_clock(freq: Duration, clock: C) -> Self {
+        let freq_nanos = freq.as_nanos() as u64;
+        assert!(
+            freq_nanos > 0,
+            "Time step frequency must be greater than zero"
+        );
+        let now = clock.now();
         Self {
-            freq_nanos: (freq_msec as u64) * 1_000_000, // Convert to nanoseconds
+            clock,
+            freq_nanos,
             max_missed: 1,
-            last_inst: Instant::now(),
+            last_inst: now,
             elapsed_nanos: 0,
+            paused: false,
+            max_frame_nanos: None,
+            total_steps: 0,
+            total_elapsed_nanos: 0,
+            dropped_steps: 0,
+            delta_inst: now,
         }
     }
     /// Sets how many missed steps will be attempted to run before giving up and discarding them.
+    /// Each tick runs at most `1 + max_miss` steps: the step that is due, plus up to `max_miss`
+    /// catch-up steps. If whole steps are still pending after that, all of the remaining
+    /// accumulated time is discarded (see dropped_steps). For example, with 3 steps pending, a
+    /// value of 0 runs 1 step and drops 2, a value of 1 runs 2 steps and drops 1, and a value of 3
+    /// runs all 3 steps.
+    ///
     /// By default, this is set to 1 (if the simulation is one step behind, it will try to catch up).
     /// Setting this too high in situations where the simulator runs longer than the frequency will
     /// cause OpenGL render frames to be missed (no screen updates).
@@ -38,38 +97,396 @@ impl TimeStep {
         self.max_missed = max_miss;
         self
     }
+    /// Limits how much time can be added to the simulation in a single frame. This prevents long
+    /// stalls (ex. the process being suspended or stopped in a debugger) from producing a large
+    /// amount of catch-up time. By default, there is no limit.
+    pub fn max_frame_time(mut self, max_time: Duration) -> Self {
+        self.max_frame_nanos = Some(max_time.as_nanos() as u64);
+        self
+    }
+
+    /// Changes the frequency (in milliseconds) of the time step. Any time that has already
+    /// accumulated is kept (see set_freq for more information).
+    pub fn set_freq_ms(&mut self, freq_msec: u32) {
+        self.set_freq(Duration::from_millis(freq_msec as u64));
+    }
+    /// Changes the frequency of the time step. Any time that has already accumulated is kept. If
+    /// the new frequency is shorter than the accumulated time, then the next tick will run multiple
+    /// catch-up steps (limited by max_missed_steps_before_discard). If it is longer, then the next
+    /// tick will simply wait until enough time has accumulated.
+    pub fn set_freq(&mut self, freq: Duration) {
+        let freq_nanos = freq.as_nanos() as u64;
+        assert!(
+            freq_nanos > 0,
+            "Time step frequency must be greater than zero"
+        );
+        self.freq_nanos = freq_nanos;
+    }
 
     fn update_elapsed(&mut self) {
-        let now = Instant::now();
+        let now = self.clock.now();
+        let e_nanos = self.new_elapsed_nanos(now);
+        self.last_inst = now;
+        self.elapsed_nanos += e_nanos;
+    }
+    /// Time that will be added to the accumulator by the next update.
+    fn new_elapsed_nanos(&self, now: Instant) -> u64 {
+        if self.paused {
+            return 0;
+        }
         let e = now.duration_since(self.last_inst);
 
         // 586,298,884 elapse, before 1500,000,000
-        self.last_inst = now;
-        self.elapsed_nanos += (e.as_secs() * 1_000_000_000) + (e.subsec_nanos() as u64);
+        let mut e_nanos = (e.as_secs() * 1_000_000_000) + (e.subsec_nanos() as u64);
+        if let Some(max_nanos) = self.max_frame_nanos {
+            e_nanos = e_nanos.min(max_nanos);
+        }
+        e_nanos
+    }
+
+    /// Restarts the time step, discarding any accumulated time. This is useful after long pauses
+    /// in the render loop (ex. loading a new level) to prevent a backlog of catch-up steps.
+    pub fn reset(&mut self) {
+        self.elapsed_nanos = 0;
+        self.last_inst = self.clock.now();
+    }
+    /// Similar to reset, but keeps the partial step that has accumulated so far. Any whole steps
+    /// that are waiting to be run are discarded.
+    pub fn reset_preserving_phase(&mut self) {
+        self.update_elapsed();
+        self.elapsed_nanos %= self.freq_nanos;
+    }
+
+    /// Pauses the simulation. While paused, calls to tick will not invoke the callback and the time
+    /// spent paused is not counted towards the next step. Any time accumulated before pausing is
+    /// kept and will be used once the time step is resumed.
+    pub fn pause(&mut self) {
+        if !self.paused {
+            self.update_elapsed();
+            self.paused = true;
+        }
+    }
+    /// Resumes a paused simulation. The time spent paused is discarded, so resuming will not cause
+    /// a burst of catch-up steps.
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.update_elapsed();
+            self.paused = false;
+        }
+    }
+    /// Indicates if the simulation is currently paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused
     }
 
     /// Indicates the start of a new frame. This will invoke the callback if the frequency time has
     /// elapsed. Note that if the max_missed_steps_before_discard is greater than zero, then the
     /// callback may be called multiple times to catch-up with any missed steps.
-    pub fn tick<F>(&mut self, mut callback: F)
+    ///
+    /// Returns the number of times the callback was invoked (zero if not enough time has elapsed).
+    pub fn tick<F>(&mut self, mut callback: F) -> u32
     where
         F: FnMut(),
+    {
+        self.tick_dt(|_| callback())
+    }
+
+    /// Same as tick, but the callback is given the duration of a single step. This avoids having to
+    /// keep the simulation's step size in sync with the time step frequency.
+    ///
+    /// ```
+    /// sim_step.tick_dt(|dt| world.advance(dt));
+    /// ```
+    pub fn tick_dt<F>(&mut self, mut callback: F) -> u32
+    where
+        F: FnMut(Duration),
     {
         self.update_elapsed();
+        if self.paused {
+            return 0;
+        }
+
+        // Run the step that is due plus up to max_missed catch-up steps. If more than that are
+        // pending, the remaining time (including any partial step) is discarded.
+        let pending = self.elapsed_nanos / self.freq_nanos;
+        let max_steps = self.max_missed as u64 + 1;
+        let count = pending.min(max_steps);
+        if pending > max_steps {
+            self.dropped_steps += pending - max_steps;
+            self.elapsed_nanos = 0;
+        } else {
+            self.elapsed_nanos -= count * self.freq_nanos;
+        }
+
+        let dt = Duration::from_nanos(self.freq_nanos);
+        for _ in 0..count {
+            callback(dt);
+            self.total_steps += 1;
+            self.total_elapsed_nanos += self.freq_nanos;
+        }
+        count as u32
+    }
+
+    /// Returns the real amount of time that has passed since the last call to delta (or since the
+    /// time step was created). This is independent of the step frequency and does not affect the
+    /// fixed-step accumulator, so it can be used alongside tick on the same instance (ex. fixed
+    /// steps for physics and a variable delta for camera smoothing).
+    pub fn delta(&mut self) -> Duration {
+        let now = self.clock.now();
+        let d = now.duration_since(self.delta_inst);
+        self.delta_inst = now;
+        d
+    }
+
+    /// Fractional progress (from 0.0 to 1.0) towards the next simulation step, based on the time
+    /// left over after the last tick. This is useful for interpolating between the previous and
+    /// next simulation states when rendering (ex. `lerp(prev_state, next_state, step.alpha())`).
+    pub fn alpha(&self) -> f32 {
+        let a = self.elapsed_nanos as f32 / self.freq_nanos as f32;
+        a.clamp(0.0, 1.0)
+    }
+
+    /// Indicates if at least one whole step is overdue (see backlog_steps). Call this at the start
+    /// of a frame, before tick. If this is still true right after calling tick, or stays true over
+    /// many frames, then the simulation is not able to keep up with real time (ex. to show a
+    /// warning). This does not modify the time step.
+    pub fn is_behind(&self) -> bool {
+        self.backlog_steps() >= 1
+    }
+    /// Number of whole steps that are currently waiting to be run, including the time that has
+    /// passed since the last tick. This does not modify the time step.
+    pub fn backlog_steps(&self) -> u32 {
+        let elapsed = self.elapsed_nanos + self.new_elapsed_nanos(self.clock.now());
+        (elapsed / self.freq_nanos).min(u32::MAX as u64) as u32
+    }
+
+    /// Total number of steps that have been run over the lifetime of this time step.
+    pub fn total_steps(&self) -> u64 {
+        self.total_steps
+    }
+    /// Total amount of simulated time (the sum of all steps that have been run).
+    pub fn total_elapsed(&self) -> Duration {
+        Duration::from_nanos(self.total_elapsed_nanos)
+    }
+    /// Total number of steps that were discarded because the simulation fell too far behind (see
+    /// max_missed_steps_before_discard). A steadily increasing value indicates that the simulation
+    /// is not able to keep up with real time.
+    pub fn dropped_steps(&self) -> u64 {
+        self.dropped_steps
+    }
+}
 
-        let mut count = 0;
-        while self.elapsed_nanos >= self.freq_nanos {
-            self.elapsed_nanos -= self.freq_nanos;
+/// Source of the current time for a TimeStep, FrameLimiter or FrameStats.
+pub trait Clock {
+    fn now(&self) -> Instant;
+}
 
-            callback();
+/// Clock that uses the system time (this is the default for TimeStep, FrameLimiter and
+/// FrameStats).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
 
-            if count >= self.max_missed && self.elapsed_nanos >= self.freq_nanos {
-                // Reached maximum missed steps and there is still more to go
-                // Reset to zero and discontinue processing
-                self.elapsed_nanos = 0;
-                return;
-            }
-            count += 1;
+/// Clock that only moves forward when advanced. Clones share the same time, so a clone can be
+/// given to a TimeStep (or FrameLimiter or FrameStats) and the original used to advance it.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Rc<Cell<Instant>>,
+}
+impl ManualClock {
+    pub fn new() -> Self {
+        Self {
+            now: Rc::new(Cell::new(Instant::now())),
         }
     }
+    /// Moves the clock forward by the given amount of time.
+    pub fn advance(&self, d: Duration) {
+        self.now.set(self.now.get() + d);
+    }
+}
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ms(n: u64) -> Duration {
+        Duration::from_millis(n)
+    }
+    fn manual_step(freq: Duration) -> (ManualClock, TimeStep<ManualClock>) {
+        let clock = ManualClock::new();
+        let step = TimeStep::with_clock(freq, clock.clone());
+        (clock, step)
+    }
+
+    #[test]
+    fn set_freq_shorter_runs_catch_up_steps() {
+        let (clock, step) = manual_step(ms(10));
+        let mut step = step.max_missed_steps_before_discard(3);
+        clock.advance(ms(9));
+        assert_eq!(step.tick(|| {}), 0);
+
+        // The 9ms that has already accumulated is now 3 whole steps
+        step.set_freq(ms(3));
+        assert_eq!(step.tick(|| {}), 3);
+        assert_eq!(step.tick(|| {}), 0);
+    }
+
+    #[test]
+    fn set_freq_longer_waits() {
+        let (clock, mut step) = manual_step(ms(10));
+        clock.advance(ms(15));
+        assert_eq!(step.tick(|| {}), 1);
+
+        // 5ms left over, so another 15ms is needed before the next step
+        step.set_freq(ms(20));
+        clock.advance(ms(10));
+        assert_eq!(step.tick(|| {}), 0);
+        clock.advance(ms(5));
+        assert_eq!(step.tick(|| {}), 1);
+        assert_eq!(step.total_elapsed(), ms(30));
+    }
+
+    #[test]
+    fn max_frame_time_caps_long_stall() {
+        let (clock, step) = manual_step(ms(10));
+        let mut step = step
+            .max_missed_steps_before_discard(100)
+            .max_frame_time(ms(50));
+        clock.advance(Duration::from_secs(10));
+        let mut calls = 0;
+        assert_eq!(step.tick(|| calls += 1), 5);
+        assert_eq!(calls, 5);
+        assert_eq!(step.dropped_steps(), 0);
+        assert_eq!(step.tick(|| calls += 1), 0);
+    }
+
+    #[test]
+    fn max_missed_limits_catch_up_steps() {
+        for &(max_missed, run, dropped) in &[(0, 1, 2), (1, 2, 1), (3, 3, 0)] {
+            let (clock, step) = manual_step(ms(10));
+            let mut step = step.max_missed_steps_before_discard(max_missed);
+            clock.advance(ms(30));
+            assert_eq!(step.tick(|| {}), run, "max_missed = {}", max_missed);
+            assert_eq!(step.dropped_steps(), dropped, "max_missed = {}", max_missed);
+            assert_eq!(step.tick(|| {}), 0, "max_missed = {}", max_missed);
+        }
+    }
+
+    #[test]
+    fn discarding_drops_partial_step() {
+        let (clock, step) = manual_step(ms(10));
+        let mut step = step.max_missed_steps_before_discard(0);
+        clock.advance(ms(35));
+        assert_eq!(step.tick(|| {}), 1);
+        assert_eq!(step.dropped_steps(), 2);
+        assert_eq!(step.alpha(), 0.0);
+
+        // The 5ms partial step was discarded as well
+        clock.advance(ms(9));
+        assert_eq!(step.tick(|| {}), 0);
+        clock.advance(ms(1));
+        assert_eq!(step.tick(|| {}), 1);
+    }
+
+    #[test]
+    fn tick_counts() {
+        let (clock, mut step) = manual_step(ms(10));
+        assert_eq!(step.tick(|| {}), 0);
+        clock.advance(ms(9));
+        assert_eq!(step.tick(|| {}), 0);
+        clock.advance(ms(1));
+        let mut dts = Vec::new();
+        assert_eq!(step.tick_dt(|dt| dts.push(dt)), 1);
+        assert_eq!(dts, vec![ms(10)]);
+        clock.advance(ms(25));
+        assert_eq!(step.tick(|| {}), 2);
+        assert_eq!(step.total_steps(), 3);
+        assert_eq!(step.total_elapsed(), ms(30));
+    }
+
+    #[test]
+    fn reset_discards_accumulated_time() {
+        let (clock, mut step) = manual_step(ms(10));
+        clock.advance(ms(25));
+        step.reset();
+        assert_eq!(step.tick(|| {}), 0);
+        assert_eq!(step.alpha(), 0.0);
+        clock.advance(ms(10));
+        assert_eq!(step.tick(|| {}), 1);
+    }
+
+    #[test]
+    fn reset_preserving_phase_keeps_partial_step() {
+        let (clock, mut step) = manual_step(ms(10));
+        clock.advance(ms(25));
+        step.reset_preserving_phase();
+        assert_eq!(step.alpha(), 0.5);
+        assert_eq!(step.tick(|| {}), 0);
+        clock.advance(ms(5));
+        assert_eq!(step.tick(|| {}), 1);
+    }
+
+    #[test]
+    fn pause_discards_paused_time() {
+        let (clock, mut step) = manual_step(ms(10));
+        clock.advance(ms(6));
+        step.pause();
+        assert!(step.is_paused());
+        clock.advance(ms(100));
+        assert_eq!(step.tick(|| {}), 0);
+
+        // The 6ms from before the pause is kept
+        step.resume();
+        assert!(!step.is_paused());
+        clock.advance(ms(3));
+        assert_eq!(step.tick(|| {}), 0);
+        clock.advance(ms(1));
+        assert_eq!(step.tick(|| {}), 1);
+        assert_eq!(step.dropped_steps(), 0);
+    }
+
+    #[test]
+    fn alpha_is_progress_to_next_step() {
+        let (clock, mut step) = manual_step(ms(10));
+        assert_eq!(step.alpha(), 0.0);
+        clock.advance(ms(12));
+        step.tick(|| {});
+        assert_eq!(step.alpha(), 0.2);
+        clock.advance(ms(5));
+        step.tick(|| {});
+        assert_eq!(step.alpha(), 0.7);
+    }
+
+    #[test]
+    fn behind_when_a_step_is_overdue() {
+        let (clock, step) = manual_step(ms(10));
+        let mut step = step.max_missed_steps_before_discard(0);
+        clock.advance(ms(9));
+        assert!(!step.is_behind());
+        clock.advance(ms(1));
+        assert!(step.is_behind());
+        assert_eq!(step.backlog_steps(), 1);
+        assert_eq!(step.tick(|| {}), 1);
+        assert!(!step.is_behind());
+
+        clock.advance(ms(25));
+        assert_eq!(step.backlog_steps(), 2);
+        assert_eq!(step.tick(|| {}), 1);
+        assert!(!step.is_behind());
+        assert_eq!(step.backlog_steps(), 0);
+    }
 }
@@ -1,10 +1,15 @@
-use crate::{Event, EventState};
+use crate::{Event, EventState, MonitorInfo};
 use glium::glutin;
 
-pub struct Window {
-    event_loop: glutin::event_loop::EventLoop<()>,
+/// Window and event loop. The type parameter is the type of custom events that can be sent to the
+/// event loop using an event loop proxy (see create_proxy).
+pub struct Window<T: 'static = ()> {
+    event_loop: glutin::event_loop::EventLoop<T>,
     pub display: glium::Display,
     pub event_state: EventState,
+    builder: WindowBuilder,
+    #[cfg(feature = "gilrs")]
+    gilrs: Option<gilrs::Gilrs>,
 }
 impl Window {
     pub fn create(title: &str) -> WindowBuilder {
@@ -14,55 +19,437 @@ impl Window {
             logical: true,
             vsync: false,
             depth_buffer_bits: None,
+            multisampling: 0,
+            srgb: true,
+            gl_profile: None,
+            gl_request: None,
             icon: None,
+            fullscreen: FullscreenMode::Windowed,
+            resizable: true,
+            decorations: true,
+            maximized: false,
+            clear_color: [0.0, 0.0, 0.0, 1.0],
+            min_inner_size: None,
+            max_inner_size: None,
         }
     }
 
+    /// Turns vsync on or off. The swap interval cannot be changed for an existing OpenGL context,
+    /// so this replaces the window with a new one (using the current size, position and fullscreen
+    /// mode of the window). Textures, buffers, and other resources are shared with the new context
+    /// and remain valid.
+    ///
+    /// winit does not report the other window settings, so the new window uses the values from the
+    /// window builder for these (ex. a title changed with set_title is reset, and a maximized
+    /// window is recreated at the same size but is no longer marked as maximized).
+    ///
+    /// Since run does not return, this must be done between calls to run_return (ex. exit the
+    /// loop when the setting is changed, call set_vsync, and then call run_return again).
+    ///
+    /// This is only available for windows without custom user events, since glium can only
+    /// rebuild a display using an event loop of that type. Windows with user events can choose
+    /// vsync when they are created (see WindowBuilder::with_vsync).
+    pub fn set_vsync(
+        &mut self,
+        vsync: bool,
+    ) -> Result<(), glium::backend::glutin::DisplayCreationError> {
+        if vsync == self.builder.vsync {
+            return Ok(());
+        }
+        let (old_id, position) = {
+            let gl_window = self.display.gl_window();
+            let window = gl_window.window();
+            let size = window.inner_size();
+            self.builder.inner_dim = [size.width as f32, size.height as f32];
+            self.builder.logical = false;
+            self.builder.fullscreen = FullscreenMode::from_glutin(window.fullscreen());
+            // The current size is used instead
+            self.builder.maximized = false;
+            (window.id(), window.outer_position().ok())
+        };
+        self.builder.vsync = vsync;
+
+        let (window, context) = self.builder.glutin_builders();
+        self.display.rebuild(window, context, &self.event_loop)?;
+        if let (Some(pos), FullscreenMode::Windowed) = (position, &self.builder.fullscreen) {
+            self.display.gl_window().window().set_outer_position(pos);
+        }
+        self.event_state.replace_window(old_id, &self.display);
+        Ok(())
+    }
+}
+impl<T: Clone + 'static> Window<T> {
+    /// Opens another window that shares the event loop with the main window. This must be done
+    /// before calling run. The new window is tracked by the event state, and events for it can be
+    /// identified by the win_id field (compare against `display.gl_window().window().id()`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut win = glu::Window::create("Main").create();
+    /// let tools = win.create_additional(glu::Window::create("Tools"));
+    /// let tools_id = tools.gl_window().window().id();
+    /// ```
+    pub fn create_additional(&mut self, builder: WindowBuilder) -> glium::Display {
+        let display = builder.build_display(&self.event_loop);
+        self.event_state.add_window(&display);
+        display
+    }
+
+    /// Indicates if vsync is enabled for the window.
+    pub fn is_vsync(&self) -> bool {
+        self.builder.vsync
+    }
+
+    /// Creates a proxy that can be used to send custom events to the event loop from other threads
+    /// (ex. when a background task completes). The events are delivered to the run callback as
+    /// Event::User, and will wake up the event loop if it is waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let win = glu::Window::create("Example").create_with_user_events::<MyMsg>();
+    /// let proxy = win.create_proxy();
+    /// std::thread::spawn(move || {
+    ///     let _ = proxy.send_event(MyMsg::AssetsLoaded);
+    /// });
+    /// ```
+    pub fn create_proxy(&self) -> glutin::event_loop::EventLoopProxy<T> {
+        self.event_loop.create_proxy()
+    }
+
+    /// Lists all available monitors along with their supported video modes (ex. for a monitor or
+    /// resolution picker).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// for monitor in win.available_monitors() {
+    ///     for mode in &monitor.video_modes {
+    ///         println!("{:?} {:?} @ {} Hz", monitor.name, mode.physical_size, mode.refresh_rate);
+    ///     }
+    /// }
+    /// ```
+    pub fn available_monitors(&self) -> Vec<MonitorInfo> {
+        self.event_loop
+            .available_monitors()
+            .map(MonitorInfo::from_handle)
+            .collect()
+    }
+    /// Monitor that the main window is currently on (if it can be determined).
+    pub fn current_monitor(&self) -> Option<MonitorInfo> {
+        self.display
+            .gl_window()
+            .window()
+            .current_monitor()
+            .map(MonitorInfo::from_handle)
+    }
+
     pub fn run(
         self,
-        mut action: impl FnMut(&glium::Display, Event, &EventState) -> WindowState + 'static,
+        mut action: impl FnMut(&glium::Display, Event<T>, &EventState) -> WindowState + 'static,
     ) -> ! {
         let display = self.display;
         let mut event_state = self.event_state;
+        #[cfg(feature = "gilrs")]
+        let mut gilrs = self.gilrs;
         self.event_loop
             .run(move |event, _win_target, control_flow| {
                 let e = Event::from_gl(&event, &mut event_state);
 
-                match action(&display, e, &event_state) {
-                    WindowState::Run => *control_flow = glutin::event_loop::ControlFlow::Poll,
-                    WindowState::Wait => *control_flow = glutin::event_loop::ControlFlow::Wait,
-                    WindowState::WaitUntil(t) => {
-                        *control_flow = glutin::event_loop::ControlFlow::WaitUntil(t)
+                let state = dispatch(&display, e, &mut event_state, &mut action);
+                #[cfg(feature = "gilrs")]
+                let state = match event {
+                    glutin::event::Event::MainEventsCleared => {
+                        poll_gamepads(&mut gilrs, &display, &mut event_state, &mut action, state)
+                    }
+                    _ => state,
+                };
+                *control_flow = state.control_flow();
+            });
+    }
+
+    /// Same as run, but also manages the frame of the main window. Before the callback is called
+    /// for an Event::Redraw, the frame is started and cleared to the clear color (see
+    /// WindowBuilder::with_clear_color), and the depth buffer is cleared to 1.0 if one was requested
+    /// using WindowBuilder::with_depth_buffer. The frame is passed to the callback, and is finished
+    /// (swapping the buffers) once the callback returns.
+    ///
+    /// At most one frame is drawn per loop iteration: for a redraw request of the main window, or
+    /// at the end of the iteration if the main window was not redrawn yet. The frame is None for all
+    /// other events, including the redraw requests of additional windows.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// win.run_with_frame(move |display, e, event_state, frame| {
+    ///     if let Some(target) = frame {
+    ///         ascii_text.draw_white(display, target, b"Hello", 2.0, [10.0, 10.0]);
+    ///     }
+    ///     glu::WindowState::Run
+    /// });
+    /// ```
+    pub fn run_with_frame(
+        self,
+        mut action: impl FnMut(&glium::Display, Event<T>, &EventState, Option<&mut glium::Frame>) -> WindowState
+            + 'static,
+    ) -> ! {
+        use glium::Surface;
+
+        let [r, g, b, a] = self.builder.clear_color;
+        let clear_depth = self.builder.depth_buffer_bits.is_some();
+        let main_id = self.display.gl_window().window().id();
+        let display = self.display;
+        let mut event_state = self.event_state;
+        #[cfg(feature = "gilrs")]
+        let mut gilrs = self.gilrs;
+        // Both RedrawRequested and RedrawEventsCleared are delivered as Event::Redraw
+        let mut frame_drawn = false;
+        self.event_loop
+            .run(move |event, _win_target, control_flow| {
+                let manage_frame = match event {
+                    glutin::event::Event::NewEvents(_) => {
+                        frame_drawn = false;
+                        false
                     }
-                    WindowState::WaitFor(d) => {
-                        let t = std::time::Instant::now() + d;
-                        *control_flow = glutin::event_loop::ControlFlow::WaitUntil(t)
+                    glutin::event::Event::RedrawRequested(id) => id == main_id && !frame_drawn,
+                    glutin::event::Event::RedrawEventsCleared => !frame_drawn,
+                    _ => false,
+                };
+                let mut frame = if manage_frame {
+                    frame_drawn = true;
+                    let mut frame = display.draw();
+                    if clear_depth {
+                        frame.clear_color_and_depth((r, g, b, a), 1.0);
+                    } else {
+                        frame.clear_color(r, g, b, a);
                     }
-                    WindowState::Exit => *control_flow = glutin::event_loop::ControlFlow::Exit,
+                    Some(frame)
+                } else {
+                    None
+                };
+
+                let mut frame_action =
+                    |display: &glium::Display, e: Event<T>, event_state: &EventState| {
+                        let frame = match e {
+                            Event::Redraw => frame.as_mut(),
+                            _ => None,
+                        };
+                        action(display, e, event_state, frame)
+                    };
+                let e = Event::from_gl(&event, &mut event_state);
+                let state = dispatch(&display, e, &mut event_state, &mut frame_action);
+                #[cfg(feature = "gilrs")]
+                let state = match event {
+                    glutin::event::Event::MainEventsCleared => poll_gamepads(
+                        &mut gilrs,
+                        &display,
+                        &mut event_state,
+                        &mut frame_action,
+                        state,
+                    ),
+                    _ => state,
+                };
+                if let Some(frame) = frame {
+                    frame.finish().expect("Failed to finish frame");
                 }
+                *control_flow = state.control_flow();
+            });
+    }
+
+    /// Same as run, but returns once the callback returns WindowState::Exit. This allows cleanup
+    /// to be performed afterwards, or the window to be used as part of a larger program (such as
+    /// an integration test).
+    ///
+    /// This is only available on Windows, macOS, Android, Linux, and the BSDs. Note that on some
+    /// platforms, the event loop will not return while the window is being resized or moved, and
+    /// the callback is the only code that will run during that time. Prefer run unless returning
+    /// control is required.
+    #[cfg(any(
+        target_os = "windows",
+        target_os = "macos",
+        target_os = "android",
+        target_os = "linux",
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd"
+    ))]
+    pub fn run_return(
+        &mut self,
+        mut action: impl FnMut(&glium::Display, Event<T>, &EventState) -> WindowState,
+    ) {
+        use glutin::platform::run_return::EventLoopExtRunReturn;
+
+        let display = &self.display;
+        let event_state = &mut self.event_state;
+        #[cfg(feature = "gilrs")]
+        let gilrs = &mut self.gilrs;
+        self.event_loop
+            .run_return(|event, _win_target, control_flow| {
+                let e = Event::from_gl(&event, event_state);
+
+                let state = dispatch(display, e, event_state, &mut action);
+                #[cfg(feature = "gilrs")]
+                let state = match event {
+                    glutin::event::Event::MainEventsCleared => {
+                        poll_gamepads(gilrs, display, event_state, &mut action, state)
+                    }
+                    _ => state,
+                };
+                *control_flow = state.control_flow();
             });
     }
 }
 
+/// Passes the event to the callback, followed by any events generated by the event state while
+/// processing it (ex. MouseClick).
+fn dispatch<T>(
+    display: &glium::Display,
+    e: Event<T>,
+    event_state: &mut EventState,
+    action: &mut impl FnMut(&glium::Display, Event<T>, &EventState) -> WindowState,
+) -> WindowState {
+    let mut state = action(display, e, event_state).request_redraw(display);
+    while let Some(e) = event_state.next_pending_event() {
+        state = state.combine(action(display, e, event_state).request_redraw(display));
+    }
+    state
+}
+
+/// Passes all gamepad events received since the last poll to the callback. This is done once per
+/// loop iteration, so gamepad input may be delayed while using WindowState::Wait.
+#[cfg(feature = "gilrs")]
+fn poll_gamepads<T>(
+    gilrs: &mut Option<gilrs::Gilrs>,
+    display: &glium::Display,
+    event_state: &mut EventState,
+    action: &mut impl FnMut(&glium::Display, Event<T>, &EventState) -> WindowState,
+    mut state: WindowState,
+) -> WindowState {
+    if let Some(gilrs) = gilrs.as_mut() {
+        while let Some(evt) = gilrs.next_event() {
+            if let Some(e) = Event::from_gilrs(&evt) {
+                state = state.combine(dispatch(display, e, event_state, action));
+            }
+        }
+    }
+    state
+}
+
+/// Provides the native handle of the main window (requires the `raw-window-handle` feature). This
+/// allows other rendering libraries to draw to the window. The raw-window-handle version used by
+/// winit 0.24 (0.3) does not have a separate display handle, as it is part of the window handle.
+///
+/// # Safety
+///
+/// The handle is only valid while the window exists. It must not be used after the Window (or the
+/// display, once moved into run) is dropped, or after the display is rebuilt by set_vsync.
+#[cfg(feature = "raw-window-handle")]
+unsafe impl<T: 'static> raw_window_handle::HasRawWindowHandle for Window<T> {
+    fn raw_window_handle(&self) -> raw_window_handle::RawWindowHandle {
+        use raw_window_handle::HasRawWindowHandle;
+        self.display.gl_window().window().raw_window_handle()
+    }
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
 pub enum WindowState {
     Run,
     /// When the current loop iteration finishes, suspend the thread until another event arrives.
     Wait,
+    /// Same as Wait, but first requests that the window be redrawn (see DisplayExt::request_redraw).
+    /// Use this when a non-redraw event changes what is displayed, so the change is painted
+    /// immediately rather than when the next event arrives. The redraw request is made as soon as
+    /// the callback returns, so it is not lost if a later event returns a different state. Like
+    /// other redraw requests, multiple requests before the redraw result in a single Event::Redraw.
+    WaitThenRedraw,
     /// When the current loop iteration finishes, suspend the thread until either another event
     /// arrives or the given time is reached.
     WaitUntil(std::time::Instant),
     WaitFor(std::time::Duration),
     Exit,
 }
+impl WindowState {
+    /// Combines the states returned while handling multiple events (an exit request is kept,
+    /// otherwise the most recent state is used).
+    fn combine(self, next: WindowState) -> WindowState {
+        if self == WindowState::Exit {
+            self
+        } else {
+            next
+        }
+    }
+    /// Requests a redraw if the state is WaitThenRedraw.
+    fn request_redraw(self, display: &glium::Display) -> WindowState {
+        if self == WindowState::WaitThenRedraw {
+            display.gl_window().window().request_redraw();
+        }
+        self
+    }
+    fn control_flow(self) -> glutin::event_loop::ControlFlow {
+        match self {
+            WindowState::Run => glutin::event_loop::ControlFlow::Poll,
+            WindowState::Wait | WindowState::WaitThenRedraw => {
+                glutin::event_loop::ControlFlow::Wait
+            }
+            WindowState::WaitUntil(t) => glutin::event_loop::ControlFlow::WaitUntil(t),
+            WindowState::WaitFor(d) => {
+                let t = std::time::Instant::now() + d;
+                glutin::event_loop::ControlFlow::WaitUntil(t)
+            }
+            WindowState::Exit => glutin::event_loop::ControlFlow::Exit,
+        }
+    }
+}
+
+/// Fullscreen mode of a window. Use Window::available_monitors to find the monitors and their video
+/// modes (resolution, bit depth, and refresh rate), and then MonitorInfo::borderless or
+/// VideoModeInfo::exclusive to get the fullscreen mode.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FullscreenMode {
+    /// Normal window (not fullscreen).
+    Windowed,
+    /// Borderless window covering the entire monitor (the current monitor is used if None).
+    Borderless(Option<glutin::monitor::MonitorHandle>),
+    /// Exclusive fullscreen using the given video mode (this may change the monitor resolution).
+    Exclusive(glutin::monitor::VideoMode),
+}
+impl FullscreenMode {
+    pub(crate) fn from_glutin(fullscreen: Option<glutin::window::Fullscreen>) -> Self {
+        match fullscreen {
+            None => FullscreenMode::Windowed,
+            Some(glutin::window::Fullscreen::Borderless(m)) => FullscreenMode::Borderless(m),
+            Some(glutin::window::Fullscreen::Exclusive(v)) => FullscreenMode::Exclusive(v),
+        }
+    }
+    pub(crate) fn into_glutin(self) -> Option<glutin::window::Fullscreen> {
+        match self {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless(m) => Some(glutin::window::Fullscreen::Borderless(m)),
+            FullscreenMode::Exclusive(v) => Some(glutin::window::Fullscreen::Exclusive(v)),
+        }
+    }
+}
 
 pub struct WindowBuilder {
     title: String,
     inner_dim: [f32; 2],
     logical: bool,
     depth_buffer_bits: Option<u8>,
+    multisampling: u16,
+    srgb: bool,
+    gl_profile: Option<glutin::GlProfile>,
+    gl_request: Option<glutin::GlRequest>,
     vsync: bool,
     icon: Option<glutin::window::Icon>,
+    fullscreen: FullscreenMode,
+    resizable: bool,
+    decorations: bool,
+    maximized: bool,
+    clear_color: [f32; 4],
+    min_inner_size: Option<glutin::dpi::Size>,
+    max_inner_size: Option<glutin::dpi::Size>,
 }
 impl WindowBuilder {
     pub fn with_inner_logical(mut self, dim: [f32; 2]) -> Self {
@@ -75,6 +462,30 @@ impl WindowBuilder {
         self.logical = false;
         self
     }
+    /// Sets the minimum size of the window contents. Note that some platforms and window managers
+    /// may not enforce this.
+    pub fn with_min_inner_logical(mut self, dim: [f32; 2]) -> Self {
+        self.min_inner_size = Some(logical_size(dim));
+        self
+    }
+    /// Sets the minimum size of the window contents. Note that some platforms and window managers
+    /// may not enforce this.
+    pub fn with_min_inner_physical(mut self, dim: [u32; 2]) -> Self {
+        self.min_inner_size = Some(physical_size(dim));
+        self
+    }
+    /// Sets the maximum size of the window contents. Note that some platforms and window managers
+    /// may not enforce this.
+    pub fn with_max_inner_logical(mut self, dim: [f32; 2]) -> Self {
+        self.max_inner_size = Some(logical_size(dim));
+        self
+    }
+    /// Sets the maximum size of the window contents. Note that some platforms and window managers
+    /// may not enforce this.
+    pub fn with_max_inner_physical(mut self, dim: [u32; 2]) -> Self {
+        self.max_inner_size = Some(physical_size(dim));
+        self
+    }
     pub fn with_vsync(mut self, vsync: bool) -> Self {
         self.vsync = vsync;
         self
@@ -83,6 +494,44 @@ impl WindowBuilder {
         self.depth_buffer_bits = Some(bits);
         self
     }
+    /// Requests multisample anti-aliasing with the given number of samples (0, 2, 4, 8 or 16). A
+    /// value of 0 disables multisampling (the default). The actual number of samples may be
+    /// limited by the driver.
+    pub fn with_multisampling(mut self, samples: u16) -> Self {
+        assert!(
+            samples == 0 || ((2..=16).contains(&samples) && samples.is_power_of_two()),
+            "Multisampling samples must be 0, 2, 4, 8 or 16"
+        );
+        self.multisampling = samples;
+        self
+    }
+    /// Sets if an sRGB framebuffer is requested (the default is true, which matches glutin). When
+    /// enabled, shader output (including AsciiText colors) is treated as linear and converted to
+    /// sRGB when written, so alpha blending happens in linear space. When disabled, colors are
+    /// written and blended as-is.
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+    /// Sets the OpenGL profile (core or compatibility) to request. By default, the profile is
+    /// chosen by glutin.
+    pub fn with_gl_profile(mut self, profile: glutin::GlProfile) -> Self {
+        self.gl_profile = Some(profile);
+        self
+    }
+    /// Sets the OpenGL version to request (the default is the latest available version).
+    pub fn with_gl_version(mut self, request: glutin::GlRequest) -> Self {
+        self.gl_request = Some(request);
+        self
+    }
+    /// Requests a core profile context for the given OpenGL version (ex. 3.3).
+    pub fn with_core(self, major: u8, minor: u8) -> Self {
+        self.with_gl_profile(glutin::GlProfile::Core)
+            .with_gl_version(glutin::GlRequest::Specific(
+                glutin::Api::OpenGl,
+                (major, minor),
+            ))
+    }
     pub fn with_glutin_icon(mut self, icon: glutin::window::Icon) -> Self {
         self.icon = Some(icon);
         self
@@ -91,33 +540,108 @@ impl WindowBuilder {
         let icon = glutin::window::Icon::from_rgba(rgba, width, height).expect("Invalid icon");
         self.with_glutin_icon(icon)
     }
+    pub fn with_fullscreen(mut self, mode: FullscreenMode) -> Self {
+        self.fullscreen = mode;
+        self
+    }
+    /// Sets if the user can resize the window (the default is true).
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+    /// Sets if the window has a title bar and borders (the default is true).
+    pub fn with_decorations(mut self, decorations: bool) -> Self {
+        self.decorations = decorations;
+        self
+    }
+    /// Sets if the window starts maximized (the default is false).
+    pub fn with_maximized(mut self, maximized: bool) -> Self {
+        self.maximized = maximized;
+        self
+    }
+    /// Sets the RGBA color that each frame is cleared to by Window::run_with_frame (the default is
+    /// black).
+    pub fn with_clear_color(mut self, color: [f32; 4]) -> Self {
+        self.clear_color = color;
+        self
+    }
     pub fn create(self) -> Window {
+        self.create_with_user_events()
+    }
+    /// Creates the window with support for custom events (see Window::create_proxy).
+    pub fn create_with_user_events<T: 'static>(self) -> Window<T> {
+        let event_loop = glutin::event_loop::EventLoop::with_user_event();
+        let display = self.build_display(&event_loop);
+        let event_state = EventState::new(&display);
+        Window {
+            event_loop,
+            display,
+            event_state,
+            builder: self,
+            #[cfg(feature = "gilrs")]
+            gilrs: gilrs::Gilrs::new().ok(),
+        }
+    }
+    fn build_display<T>(&self, event_loop: &glutin::event_loop::EventLoop<T>) -> glium::Display {
+        let (window, context) = self.glutin_builders();
+        glium::Display::new(window, context, event_loop).unwrap()
+    }
+    fn glutin_builders(
+        &self,
+    ) -> (
+        glutin::window::WindowBuilder,
+        glutin::ContextBuilder<'static, glutin::NotCurrent>,
+    ) {
         let size = if self.logical {
-            glutin::dpi::Size::Logical(glutin::dpi::LogicalSize {
-                width: self.inner_dim[0] as f64,
-                height: self.inner_dim[1] as f64,
-            })
+            logical_size(self.inner_dim)
         } else {
-            glutin::dpi::Size::Physical(glutin::dpi::PhysicalSize {
-                width: self.inner_dim[0] as u32,
-                height: self.inner_dim[1] as u32,
-            })
+            physical_size([self.inner_dim[0] as u32, self.inner_dim[1] as u32])
         };
-        let event_loop = glutin::event_loop::EventLoop::new();
         let window = glutin::window::WindowBuilder::new()
             .with_inner_size(size)
             .with_title(&self.title)
-            .with_window_icon(self.icon);
-        let mut context = glutin::ContextBuilder::new().with_vsync(self.vsync);
+            .with_window_icon(self.icon.clone())
+            .with_fullscreen(self.fullscreen.clone().into_glutin())
+            .with_resizable(self.resizable)
+            .with_decorations(self.decorations)
+            .with_maximized(self.maximized);
+        let window = match self.min_inner_size {
+            Some(min_size) => window.with_min_inner_size(min_size),
+            None => window,
+        };
+        let window = match self.max_inner_size {
+            Some(max_size) => window.with_max_inner_size(max_size),
+            None => window,
+        };
+        let mut context = glutin::ContextBuilder::new()
+            .with_vsync(self.vsync)
+            .with_srgb(self.srgb);
         if let Some(bits) = self.depth_buffer_bits {
             context = context.with_depth_buffer(bits);
         }
-        let display = glium::Display::new(window, context, &event_loop).unwrap();
-        let event_state = EventState::new(&display);
-        Window {
-            event_loop,
-            display,
-            event_state,
+        if let Some(profile) = self.gl_profile {
+            context = context.with_gl_profile(profile);
         }
+        if let Some(request) = self.gl_request {
+            context = context.with_gl(request);
+        }
+        if self.multisampling > 0 {
+            context = context.with_multisampling(self.multisampling);
+        }
+        (window, context)
     }
 }
+
+fn logical_size(dim: [f32; 2]) -> glutin::dpi::Size {
+    glutin::dpi::Size::Logical(glutin::dpi::LogicalSize {
+        width: dim[0] as f64,
+        height: dim[1] as f64,
+    })
+}
+
+fn physical_size(dim: [u32; 2]) -> glutin::dpi::Size {
+    glutin::dpi::Size::Physical(glutin::dpi::PhysicalSize {
+        width: dim[0],
+        height: dim[1],
+    })
+}